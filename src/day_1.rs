@@ -1,23 +1,20 @@
 use std::collections::HashMap;
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
+pub fn solve_a(input: &[&str]) -> usize {
     let parse_calibration_value = |line: &str| -> usize {
         let mut digits = line.chars().filter_map(|c| c.to_digit(10));
         let first_digit = digits
             .next()
             .expect("calibration string should contain a digit");
-        let last_digit = digits.last().unwrap_or(first_digit);
+        let last_digit = digits.next_back().unwrap_or(first_digit);
 
         (first_digit * 10 + last_digit) as usize
     };
 
-    input
-        .into_iter()
-        .map(|line| parse_calibration_value(line))
-        .sum()
+    input.iter().map(|line| parse_calibration_value(line)).sum()
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
+pub fn solve_b(input: &[&str]) -> usize {
     let map = HashMap::from([
         ("1", 1),
         ("2", 2),
@@ -57,10 +54,7 @@ pub fn solve_b(input: &Vec<&str>) -> usize {
         first_digit * 10 + last_digit
     };
 
-    input
-        .into_iter()
-        .map(|line| parse_calibration_value(line))
-        .sum()
+    input.iter().map(|line| parse_calibration_value(line)).sum()
 }
 
 #[cfg(test)]
@@ -1,64 +1,163 @@
 use std::cmp;
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Clone, Copy)]
-enum CubeColour {
+/// Points at the token that couldn't be parsed, as a byte offset into the parsed line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    offset: usize,
+    expected: &'static str,
+    found: String,
+}
+
+impl ParseError {
+    fn new(offset: usize, expected: &'static str, found: &str) -> Self {
+        ParseError {
+            offset,
+            expected,
+            found: found.to_string(),
+        }
+    }
+
+    fn shifted(self, by: usize) -> Self {
+        ParseError {
+            offset: self.offset + by,
+            ..self
+        }
+    }
+
+    /// Renders `line` with a caret underline beneath the offending token.
+    pub fn underline(&self, line: &str) -> String {
+        let column = line[..self.offset].chars().count();
+        let width = cmp::max(self.found.chars().count(), 1);
+        format!("{}\n{}{}", line, " ".repeat(column), "^".repeat(width))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.found.is_empty() {
+            write!(
+                f,
+                "expected {} at byte {}, found nothing",
+                self.expected, self.offset
+            )
+        } else {
+            write!(
+                f,
+                "expected {} at byte {}, found '{}'",
+                self.expected, self.offset, self.found
+            )
+        }
+    }
+}
+
+/// Splits `s` on `delimiter`, pairing each piece with its byte offset in `s`.
+fn split_with_offsets(s: &str, delimiter: char) -> impl Iterator<Item = (usize, &str)> {
+    s.split(delimiter).scan(0, move |offset, part| {
+        let start = *offset;
+        *offset += part.len() + delimiter.len_utf8();
+        Some((start, part))
+    })
+}
+
+/// Trims whitespace from `s`, keeping `offset` pointing at the first remaining byte.
+fn trim_with_offset((offset, s): (usize, &str)) -> (usize, &str) {
+    let trimmed = s.trim_start();
+    (offset + s.len() - trimmed.len(), trimmed.trim_end())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeColour {
     Red = 0,
     Green = 1,
     Blue = 2,
 }
 
+impl CubeColour {
+    pub const ALL: [CubeColour; 3] = [CubeColour::Red, CubeColour::Green, CubeColour::Blue];
+}
+
 impl std::str::FromStr for CubeColour {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "red" => Ok(CubeColour::Red),
             "blue" => Ok(CubeColour::Blue),
             "green" => Ok(CubeColour::Green),
-            _ => Err(format!("'{}' is not a valid value for CubeColour", s)),
+            _ => Err(ParseError::new(0, "colour name", s)),
         }
     }
 }
 
+impl fmt::Display for CubeColour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CubeColour::Red => "red",
+            CubeColour::Green => "green",
+            CubeColour::Blue => "blue",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Game(usize);
 
 impl std::str::FromStr for Game {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let err = Err(format!("'{}' is not a valid Game ID string", s));
-        let parts: Vec<&str> = s.split(" ").collect();
-        if parts.len() != 2 || parts[0] != "Game" {
-            return err;
+        let mut parts = split_with_offsets(s, ' ');
+        let (_, keyword) = parts.next().unwrap_or((0, ""));
+        if keyword != "Game" {
+            return Err(ParseError::new(0, "'Game'", keyword));
         }
 
-        match parts[1].parse::<usize>() {
-            Ok(id) => Ok(Game(id)),
-            Err(_) => err,
+        let (offset, id) = parts.next().unwrap_or((s.len(), ""));
+        let id = id
+            .parse::<usize>()
+            .map_err(|_| ParseError::new(offset, "Game id", id))?;
+
+        match parts.next() {
+            Some((offset, extra)) => Err(ParseError::new(offset, "':'", extra)),
+            None => Ok(Game(id)),
         }
     }
 }
 
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Game {}", self.0)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Handful([usize; 3]);
 
 impl std::str::FromStr for Handful {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let err_msg = format!("'{}' is not a valid Handful", s);
-
         let mut cubes = [0; 3];
-        for p in s.trim().split(",") {
-            let parts: Vec<&str> = p.trim().split(" ").collect();
-            if parts.len() != 2 {
-                return Err(err_msg);
-            }
+        for part in split_with_offsets(s, ',') {
+            let (part_offset, p) = trim_with_offset(part);
+            let mut parts = split_with_offsets(p, ' ');
+
+            let (_, quantity) = parts.next().unwrap_or((0, ""));
+            let quantity = quantity
+                .parse::<usize>()
+                .map_err(|_| ParseError::new(part_offset, "quantity", quantity))?;
 
-            let quantity = parts[0].parse::<usize>().map_err(|_| err_msg.clone())?;
-            let colour = CubeColour::from_str(parts[1]).map_err(|_| err_msg.clone())?;
+            let (offset, colour) = parts.next().unwrap_or((p.len(), ""));
+            let colour =
+                CubeColour::from_str(colour).map_err(|e| e.shifted(part_offset + offset))?;
+
+            if let Some((offset, extra)) = parts.next() {
+                return Err(ParseError::new(part_offset + offset, "',' or ';'", extra));
+            }
 
             cubes[colour as usize] += quantity;
         }
@@ -67,26 +166,49 @@ impl std::str::FromStr for Handful {
     }
 }
 
+impl fmt::Display for Handful {
+    /// Colours are written in red, green, blue order and colours that weren't drawn are omitted,
+    /// except that an empty handful is written as `0 red` so it still parses.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cubes: Vec<String> = CubeColour::ALL
+            .iter()
+            .filter(|colour| self.0[**colour as usize] > 0)
+            .map(|colour| format!("{} {}", self.0[*colour as usize], colour))
+            .collect();
+        if cubes.is_empty() {
+            return write!(f, "0 {}", CubeColour::Red);
+        }
+
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl Handful {
+    fn to_json(&self) -> String {
+        let cubes: Vec<String> = CubeColour::ALL
+            .iter()
+            .map(|colour| format!("\"{}\":{}", colour, self.0[*colour as usize]))
+            .collect();
+
+        format!("{{{}}}", cubes.join(","))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct GameRecord(Game, Vec<Handful>);
 
 impl std::str::FromStr for GameRecord {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let err_msg = format!("'{}' is not a valid GameRecord", s);
-
-        let parts: Vec<&str> = s.split(":").collect();
-        if parts.len() != 2 {
-            return Err(err_msg);
-        }
-
-        let game = Game::from_str(parts[0]).map_err(|_| err_msg.clone())?;
-        let handful_parts: Vec<&str> = parts[1].trim().split(";").collect();
+        let colon = s
+            .find(':')
+            .ok_or_else(|| ParseError::new(s.len(), "':'", ""))?;
+        let game = Game::from_str(&s[..colon])?;
 
         let mut handfuls = vec![];
-        for p in handful_parts {
-            let handful = Handful::from_str(p).map_err(|_| err_msg.clone())?;
+        for (offset, p) in split_with_offsets(&s[colon + 1..], ';') {
+            let handful = Handful::from_str(p).map_err(|e| e.shifted(colon + 1 + offset))?;
             handfuls.push(handful);
         }
 
@@ -94,18 +216,41 @@ impl std::str::FromStr for GameRecord {
     }
 }
 
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let handfuls: Vec<String> = self.1.iter().map(|h| h.to_string()).collect();
+        write!(f, "{}: {}", self.0, handfuls.join("; "))
+    }
+}
+
 impl GameRecord {
+    fn to_json(&self) -> String {
+        let handfuls: Vec<String> = self.1.iter().map(|h| h.to_json()).collect();
+        format!(
+            "{{\"id\":{},\"handfuls\":[{}]}}",
+            self.0 .0,
+            handfuls.join(",")
+        )
+    }
+
     fn possible_game(&self) -> bool {
+        let mut bag = [0; 3];
+        bag[CubeColour::Red as usize] = 12;
+        bag[CubeColour::Green as usize] = 13;
+        bag[CubeColour::Blue as usize] = 14;
+
+        self.possible_with(bag)
+    }
+
+    fn possible_with(&self, bag: [usize; 3]) -> bool {
         for handful in self.1.iter() {
-            let mut bag = [0; 3];
-            bag[CubeColour::Red as usize] = 12;
-            bag[CubeColour::Green as usize] = 13;
-            bag[CubeColour::Blue as usize] = 14;
-
-            for colour_idx in 0..3 {
-                if handful.0[colour_idx] > bag[colour_idx] {
-                    return false;
-                }
+            if handful
+                .0
+                .iter()
+                .zip(bag.iter())
+                .any(|(count, limit)| count > limit)
+            {
+                return false;
             }
         }
 
@@ -115,20 +260,114 @@ impl GameRecord {
     fn cubes_needed(&self) -> [usize; 3] {
         let mut max = [0; 3];
         for handful in self.1.iter() {
-            for colour_idx in 0..3 {
-                max[colour_idx] = cmp::max(max[colour_idx], handful.0[colour_idx]);
+            for (max, count) in max.iter_mut().zip(handful.0.iter()) {
+                *max = cmp::max(*max, *count);
             }
         }
 
         max
     }
+
+    /// Product of the cubes needed for just the given colours.
+    fn power(&self, colours: &[CubeColour]) -> usize {
+        let cubes_needed = self.cubes_needed();
+        colours
+            .iter()
+            .map(|colour| cubes_needed[*colour as usize])
+            .product()
+    }
+
+    /// Log-likelihood of drawing this game's handfuls from `bag`. Cubes are drawn without
+    /// replacement within a handful and put back between handfuls, so each handful is an
+    /// independent multivariate hypergeometric draw.
+    fn log_likelihood(&self, bag: [usize; 3], ln_factorials: &[f64]) -> f64 {
+        let total: usize = bag.iter().sum();
+        self.1
+            .iter()
+            .map(|handful| {
+                let drawn: usize = handful.0.iter().sum();
+                if drawn > total {
+                    // The handful can't come from this bag at all
+                    return f64::NEG_INFINITY;
+                }
+                let ways: f64 = bag
+                    .iter()
+                    .zip(handful.0.iter())
+                    .map(|(available, count)| ln_choose(ln_factorials, *available, *count))
+                    .sum();
+                ways - ln_choose(ln_factorials, total, drawn)
+            })
+            .sum()
+    }
+
+    /// Searches every bag of `size` cubes for the one that makes this game most likely, or
+    /// returns the cubes needed if they're more than `size`. The size has to be fixed: adding
+    /// cubes of every colour keeps making the handfuls more likely, so an unbounded search never
+    /// settles. Ties (within floating point noise) go to the bag with the fewest red, then green.
+    fn most_likely_bag(&self, size: usize, ln_factorials: &[f64]) -> ([usize; 3], f64) {
+        let needed = self.cubes_needed();
+        let spare = match size.checked_sub(needed.iter().sum()) {
+            Some(spare) => spare,
+            None => return (needed, self.log_likelihood(needed, ln_factorials)),
+        };
+
+        let mut best = ([0; 3], f64::NEG_INFINITY);
+        for extra_red in 0..=spare {
+            for extra_green in 0..=spare - extra_red {
+                let extra_blue = spare - extra_red - extra_green;
+                let bag = [
+                    needed[0] + extra_red,
+                    needed[1] + extra_green,
+                    needed[2] + extra_blue,
+                ];
+                let log_likelihood = self.log_likelihood(bag, ln_factorials);
+                if log_likelihood > best.1 + 1e-12 {
+                    best = (bag, log_likelihood);
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// Natural logs of `0!` through `n!`, so binomial coefficients can be combined without overflow.
+fn ln_factorials(n: usize) -> Vec<f64> {
+    let mut results = Vec::with_capacity(n + 1);
+    results.push(0.0);
+    for i in 1..=n {
+        results.push(results[i - 1] + (i as f64).ln());
+    }
+
+    results
+}
+
+fn ln_choose(ln_factorials: &[f64], n: usize, k: usize) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+
+    ln_factorials[n] - ln_factorials[k] - ln_factorials[n - k]
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
+#[derive(Debug, PartialEq)]
+pub struct BagEstimate {
+    pub game: usize,
+    pub bag: [usize; 3],
+    pub likelihood: f64,
+}
+
+/// Parses one record per line, reporting the index of the first line that fails.
+fn parse_records(input: &[&str]) -> Result<Vec<GameRecord>, (usize, ParseError)> {
     input
         .iter()
-        .map(|line| GameRecord::from_str(line))
-        .collect::<Result<Vec<GameRecord>, _>>()
+        .enumerate()
+        .map(|(idx, line)| GameRecord::from_str(line).map_err(|e| (idx, e)))
+        .collect()
+}
+
+pub fn solve_a(input: &[&str]) -> usize {
+    parse_records(input)
         .expect("All GameRecord's should be valid")
         .iter()
         .filter(|r| r.possible_game())
@@ -136,16 +375,97 @@ pub fn solve_a(input: &Vec<&str>) -> usize {
         .sum()
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    input
+pub fn solve_b(input: &[&str]) -> usize {
+    power_sum(input, &CubeColour::ALL).expect("All GameRecord's should be valid")
+}
+
+/// Smallest bag that makes every listed game possible, or every game when `games` is `None`.
+/// Ids that don't match any game are ignored.
+pub fn minimal_bag(
+    input: &[&str],
+    games: Option<&[usize]>,
+) -> Result<[usize; 3], (usize, ParseError)> {
+    let mut bag = [0; 3];
+    for record in parse_records(input)?
         .iter()
-        .map(|line| GameRecord::from_str(line))
-        .collect::<Result<Vec<GameRecord>, _>>()
-        .expect("All GameRecord's should be valid")
+        .filter(|r| games.is_none_or(|games| games.contains(&r.0 .0)))
+    {
+        for (max, count) in bag.iter_mut().zip(record.cubes_needed().iter()) {
+            *max = cmp::max(*max, *count);
+        }
+    }
+
+    Ok(bag)
+}
+
+/// Ids of the games that couldn't have been played with `bag`.
+pub fn ruled_out(input: &[&str], bag: [usize; 3]) -> Result<Vec<usize>, (usize, ParseError)> {
+    Ok(parse_records(input)?
         .iter()
-        .map(|r| r.cubes_needed())
-        .map(|cubes_needed| cubes_needed.into_iter().fold(1, |sum, i| sum * i))
-        .sum()
+        .filter(|r| !r.possible_with(bag))
+        .map(|r| r.0 .0)
+        .collect())
+}
+
+/// Sum over every game of the product of the cubes needed for `colours`.
+pub fn power_sum(input: &[&str], colours: &[CubeColour]) -> Result<usize, (usize, ParseError)> {
+    Ok(parse_records(input)?.iter().map(|r| r.power(colours)).sum())
+}
+
+/// Parses every record and exports them as a JSON array of `{"id", "handfuls"}` objects.
+pub fn to_json(input: &[&str]) -> Result<String, (usize, ParseError)> {
+    let records = parse_records(input)?;
+    let records: Vec<String> = records.iter().map(|r| r.to_json()).collect();
+
+    Ok(format!("[{}]", records.join(",")))
+}
+
+/// Number of cubes in the bag when estimating its contents, the same as part a's bag.
+pub const BAG_SIZE: usize = 12 + 13 + 14;
+
+/// Estimates the most likely bag of `size` cubes for each game (or of just the cubes needed,
+/// where a game needs more than that).
+pub fn most_likely_bags(
+    input: &[&str],
+    size: usize,
+) -> Result<Vec<BagEstimate>, (usize, ParseError)> {
+    let records = parse_records(input)?;
+    let largest_bag = records
+        .iter()
+        .map(|r| cmp::max(r.cubes_needed().iter().sum(), size))
+        .max()
+        .unwrap_or(0);
+    let ln_factorials = ln_factorials(largest_bag);
+
+    Ok(records
+        .iter()
+        .map(|r| {
+            let (bag, log_likelihood) = r.most_likely_bag(size, &ln_factorials);
+            BagEstimate {
+                game: r.0 .0,
+                bag,
+                likelihood: log_likelihood.exp(),
+            }
+        })
+        .collect())
+}
+
+/// Likelihood of each game's handfuls given a proposed `bag` of red, green and blue cubes.
+pub fn bag_likelihoods(
+    input: &[&str],
+    bag: [usize; 3],
+) -> Result<Vec<BagEstimate>, (usize, ParseError)> {
+    let records = parse_records(input)?;
+    let ln_factorials = ln_factorials(bag.iter().sum());
+
+    Ok(records
+        .iter()
+        .map(|r| BagEstimate {
+            game: r.0 .0,
+            bag,
+            likelihood: r.log_likelihood(bag, &ln_factorials).exp(),
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -165,46 +485,61 @@ mod tests {
     }
 
     #[test]
-    fn test_possible_game() {
-        assert_eq!(GameRecord(Game(0), vec![]).possible_game(), true);
-        assert_eq!(
-            GameRecord(Game(0), vec![Handful([0, 0, 0])]).possible_game(),
-            true
-        );
+    fn test_parse_errors() {
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([12, 0, 0])]).possible_game(),
-            true
+            GameRecord::from_str("Game 3: 6 purple").unwrap_err(),
+            ParseError::new(10, "colour name", "purple")
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([0, 13, 0])]).possible_game(),
-            true
+            GameRecord::from_str("Game 3: 6 red; x blue").unwrap_err(),
+            ParseError::new(15, "quantity", "x")
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([0, 0, 14])]).possible_game(),
-            true
+            GameRecord::from_str("Game 3: 6 red, 2").unwrap_err(),
+            ParseError::new(16, "colour name", "")
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([12, 13, 14])]).possible_game(),
-            true
+            GameRecord::from_str("Game three: 6 red").unwrap_err(),
+            ParseError::new(5, "Game id", "three")
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([13, 0, 0])]).possible_game(),
-            false
+            GameRecord::from_str("Gaem 3: 6 red").unwrap_err(),
+            ParseError::new(0, "'Game'", "Gaem")
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([0, 14, 0])]).possible_game(),
-            false
+            GameRecord::from_str("Game 3 6 red").unwrap_err(),
+            ParseError::new(12, "':'", "")
         );
+    }
+
+    #[test]
+    fn test_parse_error_underline() {
+        let line = "Game 3: 6 red, 2 purple";
+        let err = GameRecord::from_str(line).unwrap_err();
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([0, 0, 15])]).possible_game(),
-            false
+            err.to_string(),
+            "expected colour name at byte 17, found 'purple'"
         );
         assert_eq!(
-            GameRecord(Game(0), vec![Handful([13, 14, 15])]).possible_game(),
-            false
+            err.underline(line),
+            "Game 3: 6 red, 2 purple\n                 ^^^^^^"
         );
     }
 
+    #[test]
+    fn test_possible_game() {
+        assert!(GameRecord(Game(0), vec![]).possible_game());
+        assert!(GameRecord(Game(0), vec![Handful([0, 0, 0])]).possible_game());
+        assert!(GameRecord(Game(0), vec![Handful([12, 0, 0])]).possible_game());
+        assert!(GameRecord(Game(0), vec![Handful([0, 13, 0])]).possible_game());
+        assert!(GameRecord(Game(0), vec![Handful([0, 0, 14])]).possible_game());
+        assert!(GameRecord(Game(0), vec![Handful([12, 13, 14])]).possible_game());
+        assert!(!GameRecord(Game(0), vec![Handful([13, 0, 0])]).possible_game());
+        assert!(!GameRecord(Game(0), vec![Handful([0, 14, 0])]).possible_game());
+        assert!(!GameRecord(Game(0), vec![Handful([0, 0, 15])]).possible_game());
+        assert!(!GameRecord(Game(0), vec![Handful([13, 14, 15])]).possible_game());
+    }
+
     #[test]
     fn test_cubes_needed() {
         assert_eq!(
@@ -217,6 +552,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_display_game_record() {
+        assert_eq!(
+            GameRecord(Game(5), vec![Handful([6, 3, 1]), Handful([1, 0, 2])]).to_string(),
+            "Game 5: 6 red, 3 green, 1 blue; 1 red, 2 blue"
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let input = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 100: 6 red, 1 blue, 3 green, 26 red, 4 blue; 2 blue, 13 green, 1 red, 2 green",
+            "Game 1: 0 red",
+            "Game 2: 0 blue; 1 green",
+        ];
+        for line in input {
+            let record = GameRecord::from_str(line).unwrap();
+            assert_eq!(GameRecord::from_str(&record.to_string()).unwrap(), record);
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            to_json(&["Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red"]).unwrap(),
+            r#"[{"id":5,"handfuls":[{"red":6,"green":3,"blue":1},{"red":1,"green":0,"blue":2}]}]"#
+        );
+        assert_eq!(to_json(&[]).unwrap(), "[]");
+        assert!(to_json(&["Game 5: 6 purple"]).is_err());
+    }
+
+    #[test]
+    fn test_minimal_bag() {
+        let input = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        ];
+        assert_eq!(minimal_bag(&input, None).unwrap(), [20, 13, 6]);
+        assert_eq!(minimal_bag(&input, Some(&[1, 2])).unwrap(), [4, 3, 6]);
+        assert_eq!(minimal_bag(&input, Some(&[42])).unwrap(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_ruled_out() {
+        let input = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        ];
+        assert_eq!(ruled_out(&input, [12, 13, 14]).unwrap(), vec![3]);
+        assert_eq!(ruled_out(&input, [4, 3, 5]).unwrap(), vec![1, 3]);
+        assert!(ruled_out(&input, [20, 13, 6]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_power_sum() {
+        let input = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        ];
+        assert_eq!(power_sum(&input, &CubeColour::ALL).unwrap(), 48 + 12);
+        assert_eq!(
+            power_sum(&input, &[CubeColour::Red, CubeColour::Blue]).unwrap(),
+            24 + 4
+        );
+        assert_eq!(power_sum(&input, &[CubeColour::Green]).unwrap(), 2 + 3);
+        assert_eq!(power_sum(&input, &[]).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_bag_likelihoods() {
+        let likelihoods = |line, bag| bag_likelihoods(&[line], bag).unwrap()[0].likelihood;
+        assert!((likelihoods("Game 1: 3 blue", [0, 0, 3]) - 1.0).abs() < 1e-9);
+        assert!((likelihoods("Game 1: 1 red", [1, 1, 0]) - 0.5).abs() < 1e-9);
+        assert!((likelihoods("Game 1: 1 red; 1 green", [1, 1, 0]) - 0.25).abs() < 1e-9);
+        // 2 red from 3 red + 1 blue: C(3,2) / C(4,2)
+        assert!((likelihoods("Game 1: 2 red", [3, 0, 1]) - 0.5).abs() < 1e-9);
+        assert_eq!(likelihoods("Game 1: 4 red", [3, 0, 1]), 0.0);
+        // More cubes than the whole bag holds
+        assert_eq!(likelihoods("Game 1: 2 red", [1, 0, 0]), 0.0);
+    }
+
+    #[test]
+    fn test_most_likely_bags() {
+        let estimates = most_likely_bags(&["Game 1: 3 blue", "Game 2: 1 red; 1 blue"], 3).unwrap();
+        assert_eq!(estimates[0].game, 1);
+        assert_eq!(estimates[0].bag, [0, 0, 3]);
+        assert!((estimates[0].likelihood - 1.0).abs() < 1e-9);
+        // Two red and one blue ties with one red and two blue, and the fewest red comes first
+        assert_eq!(estimates[1].game, 2);
+        assert_eq!(estimates[1].bag, [1, 0, 2]);
+        assert!((estimates[1].likelihood - 2.0 / 9.0).abs() < 1e-9);
+
+        // The estimate keeps the same proportions as the bag grows, rather than running off to
+        // whatever limit the search has
+        let game =
+            ["Game 1: 2 green, 6 blue, 7 red; 12 green, 6 blue, 3 red; 5 red, 18 green, 4 blue"];
+        let bag = |size| most_likely_bags(&game, size).unwrap()[0].bag;
+        assert_eq!(bag(BAG_SIZE), [9, 21, 9]);
+        assert_eq!(bag(2 * BAG_SIZE), [18, 41, 19]);
+        assert_eq!(bag(4 * BAG_SIZE), [37, 80, 39]);
+
+        // Games that need more cubes than the bag size get just the cubes they need
+        let estimates = most_likely_bags(&["Game 1: 5 red, 1 green"], 2).unwrap();
+        assert_eq!(estimates[0].bag, [5, 1, 0]);
+    }
+
     #[test]
     fn test_solve_a() {
         let input = vec![
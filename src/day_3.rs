@@ -1,6 +1,8 @@
-use std::collections::HashSet;
+use std::fmt;
 use std::str::FromStr;
 
+use crate::grid::{Coord, Grid, GridError};
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum Character {
     Digit(u8),
@@ -8,172 +10,428 @@ enum Character {
     Symbol(String),
 }
 
-impl std::str::FromStr for Character {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let char = match s {
-            "." => Character::Dot,
-            potential_digit => match potential_digit.parse::<u8>() {
-                Ok(d) => Character::Digit(d),
-                _ => Character::Symbol(s.to_string()),
+impl From<char> for Character {
+    fn from(c: char) -> Self {
+        match c {
+            '.' => Character::Dot,
+            potential_digit => match potential_digit.to_digit(10) {
+                Some(d) => Character::Digit(d as u8),
+                None => Character::Symbol(c.to_string()),
             },
-        };
+        }
+    }
+}
 
-        Ok(char)
+impl fmt::Display for Character {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Character::Digit(d) => write!(f, "{}", d),
+            Character::Dot => write!(f, "."),
+            Character::Symbol(s) => write!(f, "{}", s),
+        }
     }
 }
 
+/// A run of digits in one row, labelled with its position in reading order.
+#[derive(Debug, PartialEq, Eq)]
+struct NumberSpan {
+    id: usize,
+    value: usize,
+    start: Coord,
+    /// Exclusive end column.
+    end: usize,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Schematic {
-    characters: Vec<Character>,
-    rows: usize,
-    columns: usize,
+    characters: Grid<Character>,
+    numbers: Vec<NumberSpan>,
+    /// The id of the number covering each cell, if any.
+    number_ids: Grid<Option<usize>>,
+}
+
+impl TryFrom<&[&str]> for Schematic {
+    type Error = GridError;
+
+    /// Each character (not byte) is one cell, so multibyte symbols take up a single column.
+    fn try_from(item: &[&str]) -> Result<Self, Self::Error> {
+        Ok(Schematic::new(Grid::from_lines(item, Character::from)?))
+    }
 }
 
-impl From<&Vec<&str>> for Schematic {
-    fn from(item: &Vec<&str>) -> Self {
-        let mut characters = Vec::<Character>::new();
-        let rows = item.len();
-        let columns = item.first().unwrap_or(&"").len();
-        for line in item {
-            for i in 0..line.len() {
-                let character = Character::from_str(&line[i..i + 1])
-                    .expect("Character in schematic should be valid");
-                characters.push(character);
+impl Schematic {
+    fn new(characters: Grid<Character>) -> Self {
+        let mut numbers = Vec::<NumberSpan>::new();
+        let mut number_ids = characters.map(|_| None);
+        for (row, cells) in characters.iter_rows().enumerate() {
+            let mut current: Option<NumberSpan> = None;
+            for (column, character) in cells.iter().enumerate() {
+                match (character, current.as_mut()) {
+                    (Character::Digit(d), Some(span)) => {
+                        span.value = span.value * 10 + *d as usize;
+                        span.end = column + 1;
+                    }
+                    (Character::Digit(d), None) => {
+                        current = Some(NumberSpan {
+                            id: numbers.len(),
+                            value: *d as usize,
+                            start: Coord::new(row, column),
+                            end: column + 1,
+                        });
+                    }
+                    (_, Some(_)) => numbers.extend(current.take()),
+                    (_, None) => {}
+                }
+
+                if let Some(span) = &current {
+                    number_ids[Coord::new(row, column)] = Some(span.id);
+                }
             }
+            numbers.extend(current);
         }
 
         Schematic {
             characters,
-            rows,
-            columns,
+            numbers,
+            number_ids,
         }
     }
+
+    /// Ids of the distinct numbers touching `coord`, including diagonally.
+    fn adjacent_numbers(&self, coord: Coord) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .characters
+            .neighbours8(coord)
+            .into_iter()
+            .filter_map(|c| self.number_ids[c])
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        ids
+    }
+
+    fn symbols(&self) -> impl Iterator<Item = (Coord, &str)> {
+        self.characters.iter().filter_map(|(coord, c)| match c {
+            Character::Symbol(symbol) => Some((coord, symbol.as_str())),
+            _ => None,
+        })
+    }
 }
 
-impl Schematic {
-    fn adjacencies(&self, idx: usize) -> Vec<usize> {
-        let mut results = Vec::<usize>::new();
-        let row = idx / self.columns;
-        let column = idx % self.columns;
-
-        for r in -1..=1 {
-            for c in -1..=1 {
-                if r == 0 && c == 0 {
-                    continue;
-                }
+#[derive(Debug, PartialEq, Eq)]
+enum SymbolSet {
+    Any,
+    Only(Vec<String>),
+}
 
-                let row = row as isize + r;
-                let column = column as isize + c;
-                if row >= 0
-                    && row < self.rows as isize
-                    && column >= 0
-                    && column < self.columns as isize
-                {
-                    results.push(self.columns * row as usize + column as usize);
-                }
-            }
+impl SymbolSet {
+    fn contains(&self, symbol: &str) -> bool {
+        match self {
+            SymbolSet::Any => true,
+            SymbolSet::Only(symbols) => symbols.iter().any(|s| s == symbol),
         }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NeighbourCount {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
 
-        results
+impl NeighbourCount {
+    fn matches(&self, count: usize) -> bool {
+        match self {
+            NeighbourCount::Exactly(n) => count == *n,
+            NeighbourCount::AtLeast(n) => count >= *n,
+            NeighbourCount::AtMost(n) => count <= *n,
+        }
     }
+}
 
-    fn part_number_boundaries(&self, digit_idx: usize) -> (usize, usize) {
-        let (mut west_boundary, mut east_boundary) = (digit_idx, digit_idx + 1);
-        let column = digit_idx % self.columns;
-        let (steps_west, steps_east) = (column, self.columns - column);
+#[derive(Debug, PartialEq, Eq)]
+enum Aggregation {
+    /// Every number next to a matching symbol, counted once even if it touches several.
+    Distinct,
+    Product,
+    Sum,
+    Max,
+}
 
-        for idx in (digit_idx + 1)..=(digit_idx + steps_east) {
-            match self.characters[idx] {
-                Character::Digit(_) => east_boundary = idx + 1,
-                _ => break,
-            };
+/// Picks out symbols by what they are and how many numbers surround them, then combines
+/// those numbers.
+#[derive(Debug, PartialEq, Eq)]
+struct SymbolRule {
+    symbols: SymbolSet,
+    neighbours: NeighbourCount,
+    aggregation: Aggregation,
+}
+
+impl SymbolRule {
+    /// Part a: every number adjacent to any symbol.
+    fn part_numbers() -> Self {
+        SymbolRule {
+            symbols: SymbolSet::Any,
+            neighbours: NeighbourCount::AtLeast(1),
+            aggregation: Aggregation::Distinct,
         }
+    }
 
-        for idx in ((digit_idx - steps_west)..digit_idx).rev() {
-            match self.characters[idx] {
-                Character::Digit(_) => west_boundary = idx,
-                _ => break,
-            };
+    /// Part b: the product of the two numbers around each `*` that has exactly two.
+    fn gear_ratios() -> Self {
+        SymbolRule {
+            symbols: SymbolSet::Only(vec!["*".to_string()]),
+            neighbours: NeighbourCount::Exactly(2),
+            aggregation: Aggregation::Product,
         }
+    }
 
-        (west_boundary, east_boundary)
+    /// Each symbol the rule picks out, with the ids of the numbers around it.
+    fn matching_symbols(&self, schematic: &Schematic) -> Vec<(Coord, Vec<usize>)> {
+        schematic
+            .symbols()
+            .filter(|(_, symbol)| self.symbols.contains(symbol))
+            .map(|(coord, _)| (coord, schematic.adjacent_numbers(coord)))
+            .filter(|(_, ids)| self.neighbours.matches(ids.len()))
+            .collect()
     }
 
-    fn part_number(&self, start: usize, end: usize) -> usize {
-        let mut part_number = 0;
-        for idx in start..end {
-            if let Character::Digit(d) = self.characters[idx] {
-                part_number *= 10;
-                part_number += d as usize;
+    fn apply(&self, schematic: &Schematic) -> Vec<usize> {
+        let matching = self
+            .matching_symbols(schematic)
+            .into_iter()
+            .map(|(_, ids)| ids);
+        let values = |ids: Vec<usize>| ids.into_iter().map(|id| schematic.numbers[id].value);
+
+        match self.aggregation {
+            Aggregation::Distinct => {
+                let mut selected = vec![false; schematic.numbers.len()];
+                for id in matching.flatten() {
+                    selected[id] = true;
+                }
+
+                schematic
+                    .numbers
+                    .iter()
+                    .filter(|n| selected[n.id])
+                    .map(|n| n.value)
+                    .collect()
             }
+            Aggregation::Product => matching.map(|ids| values(ids).product()).collect(),
+            Aggregation::Sum => matching.map(|ids| values(ids).sum()).collect(),
+            Aggregation::Max => matching.map(|ids| values(ids).max().unwrap_or(0)).collect(),
+        }
+    }
+}
+
+impl std::str::FromStr for SymbolRule {
+    type Err = String;
+
+    /// Either a preset name (`part-numbers` or `gears`) or `SYMBOLS COUNT AGGREGATION`, e.g.
+    /// `*# >=2 sum`. SYMBOLS is `any` or the symbol characters, COUNT is `=N`, `>=N` or `<=N`
+    /// and AGGREGATION is one of `distinct`, `product`, `sum` or `max`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "part-numbers" => return Ok(SymbolRule::part_numbers()),
+            "gears" => return Ok(SymbolRule::gear_ratios()),
+            _ => {}
         }
 
-        part_number
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 3 {
+            return Err(format!("'{}' is not a valid SymbolRule", s));
+        }
+
+        let symbols = match parts[0] {
+            "any" => SymbolSet::Any,
+            symbols => SymbolSet::Only(symbols.chars().map(|c| c.to_string()).collect()),
+        };
+
+        let count_err = || format!("'{}' is not a valid neighbour count", parts[1]);
+        let (constructor, n): (fn(usize) -> NeighbourCount, &str) =
+            if let Some(n) = parts[1].strip_prefix(">=") {
+                (NeighbourCount::AtLeast, n)
+            } else if let Some(n) = parts[1].strip_prefix("<=") {
+                (NeighbourCount::AtMost, n)
+            } else if let Some(n) = parts[1].strip_prefix('=') {
+                (NeighbourCount::Exactly, n)
+            } else {
+                return Err(count_err());
+            };
+        let neighbours = constructor(n.parse::<usize>().map_err(|_| count_err())?);
+
+        let aggregation = match parts[2] {
+            "distinct" => Aggregation::Distinct,
+            "product" => Aggregation::Product,
+            "sum" => Aggregation::Sum,
+            "max" => Aggregation::Max,
+            a => return Err(format!("'{}' is not a valid aggregation", a)),
+        };
+
+        Ok(SymbolRule {
+            symbols,
+            neighbours,
+            aggregation,
+        })
     }
 }
 
 fn part_numbers(schematic: &Schematic) -> Vec<usize> {
-    let adjacent_digit_coords = schematic
-        .characters
-        .iter()
-        .enumerate()
-        .filter(|(_, c)| matches!(c, Character::Symbol { .. }))
-        .flat_map(|(idx, _)| schematic.adjacencies(idx))
-        .filter(|idx| matches!(schematic.characters[*idx], Character::Digit { .. }));
-    let unique_digit_coords: HashSet<usize> = HashSet::from_iter(adjacent_digit_coords);
-    let part_number_boundaries = unique_digit_coords
-        .into_iter()
-        .map(|idx| schematic.part_number_boundaries(idx));
-    let unique_boundaries: HashSet<(usize, usize)> = HashSet::from_iter(part_number_boundaries);
-    unique_boundaries
-        .into_iter()
-        .map(|(start, end)| schematic.part_number(start, end))
-        .collect()
+    SymbolRule::part_numbers().apply(schematic)
 }
 
 fn gear_ratios(schematic: &Schematic) -> Vec<usize> {
-    let symbol_adjacent_digit_coords = schematic
+    SymbolRule::gear_ratios().apply(schematic)
+}
+
+pub fn solve_a(input: &[&str]) -> usize {
+    let schematic = Schematic::try_from(input).expect("Schematic should be rectangular");
+    part_numbers(&schematic).iter().sum()
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    let schematic = Schematic::try_from(input).expect("Schematic should be rectangular");
+    gear_ratios(&schematic).iter().sum()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Annotation {
+    Plain,
+    PartNumber,
+    OtherNumber,
+    Gear,
+}
+
+impl Annotation {
+    fn ansi_colour(&self) -> Option<&'static str> {
+        match self {
+            Annotation::Plain => None,
+            Annotation::PartNumber => Some("\x1b[32m"),
+            Annotation::OtherNumber => Some("\x1b[31m"),
+            Annotation::Gear => Some("\x1b[1;33m"),
+        }
+    }
+
+    fn html_class(&self) -> Option<&'static str> {
+        match self {
+            Annotation::Plain => None,
+            Annotation::PartNumber => Some("part"),
+            Annotation::OtherNumber => Some("other"),
+            Annotation::Gear => Some("gear"),
+        }
+    }
+}
+
+/// Marks every cell with whether it's part of a part number, some other number or a gear.
+fn annotate(schematic: &Schematic) -> Grid<Annotation> {
+    let mut is_part = vec![false; schematic.numbers.len()];
+    for (_, ids) in SymbolRule::part_numbers().matching_symbols(schematic) {
+        for id in ids {
+            is_part[id] = true;
+        }
+    }
+
+    let mut annotations = schematic.number_ids.map(|id| match id {
+        Some(id) if is_part[*id] => Annotation::PartNumber,
+        Some(_) => Annotation::OtherNumber,
+        None => Annotation::Plain,
+    });
+    for (coord, _) in SymbolRule::gear_ratios().matching_symbols(schematic) {
+        annotations[coord] = Annotation::Gear;
+    }
+
+    annotations
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderFormat {
+    Ansi,
+    Html,
+}
+
+/// Renders the schematic with part numbers, other numbers and gears highlighted.
+fn render(schematic: &Schematic, format: RenderFormat) -> String {
+    let annotations = annotate(schematic);
+    let mut lines = Vec::<String>::new();
+    for (characters, annotations) in schematic
         .characters
-        .iter()
-        .enumerate()
-        .filter(|(_, c)| match c {
-            Character::Symbol(x) => x == "*",
-            _ => false,
-        })
-        .map(|(idx, _)| {
-            schematic
-                .adjacencies(idx)
-                .into_iter()
-                .filter(|idx| matches!(schematic.characters[*idx], Character::Digit { .. }))
-        });
-
-    let mut result = Vec::<usize>::new();
-    for adjacencies in symbol_adjacent_digit_coords {
-        let unique_digit_coords: HashSet<usize> = HashSet::from_iter(adjacencies);
-        let part_number_boundaries = unique_digit_coords
-            .into_iter()
-            .map(|idx| schematic.part_number_boundaries(idx));
-        let unique_boundaries: HashSet<(usize, usize)> = HashSet::from_iter(part_number_boundaries);
-        let part_numbers: Vec<usize> = unique_boundaries
-            .into_iter()
-            .map(|(start, end)| schematic.part_number(start, end))
-            .collect();
-        if part_numbers.len() == 2 {
-            result.push(part_numbers[0] * part_numbers[1]);
+        .iter_rows()
+        .zip(annotations.iter_rows())
+    {
+        let mut line = String::new();
+        let mut current = Annotation::Plain;
+        for (character, annotation) in characters.iter().zip(annotations.iter()) {
+            if *annotation != current {
+                close_annotation(&mut line, current, format);
+                open_annotation(&mut line, *annotation, format);
+                current = *annotation;
+            }
+
+            match format {
+                RenderFormat::Ansi => line.push_str(&character.to_string()),
+                RenderFormat::Html => line.push_str(&escape_html(&character.to_string())),
+            }
         }
+        close_annotation(&mut line, current, format);
+        lines.push(line);
     }
 
-    result
+    match format {
+        RenderFormat::Ansi => lines.join("\n"),
+        RenderFormat::Html => format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<style>\n\
+             .part {{ color: green; }}\n\
+             .other {{ color: red; }}\n\
+             .gear {{ color: goldenrod; font-weight: bold; }}\n\
+             </style>\n</head>\n<body>\n<pre>\n{}\n</pre>\n</body>\n</html>",
+            lines.join("\n")
+        ),
+    }
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    part_numbers(&Schematic::from(input)).iter().sum()
+fn open_annotation(line: &mut String, annotation: Annotation, format: RenderFormat) {
+    match format {
+        RenderFormat::Ansi => line.push_str(annotation.ansi_colour().unwrap_or("")),
+        RenderFormat::Html => {
+            if let Some(class) = annotation.html_class() {
+                line.push_str(&format!("<span class=\"{}\">", class));
+            }
+        }
+    }
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    gear_ratios(&Schematic::from(input)).iter().sum()
+fn close_annotation(line: &mut String, annotation: Annotation, format: RenderFormat) {
+    if annotation == Annotation::Plain {
+        return;
+    }
+
+    match format {
+        RenderFormat::Ansi => line.push_str("\x1b[0m"),
+        RenderFormat::Html => line.push_str("</span>"),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders a schematic so it's easy to see which numbers `part_numbers` counted.
+pub fn render_schematic(input: &[&str], format: RenderFormat) -> Result<String, String> {
+    let schematic = Schematic::try_from(input).map_err(|e| e.to_string())?;
+
+    Ok(render(&schematic, format))
+}
+
+/// Applies a rule written as described on `SymbolRule::from_str` and sums the results.
+pub fn apply_rule(input: &[&str], rule: &str) -> Result<usize, String> {
+    let rule = SymbolRule::from_str(rule)?;
+    let schematic = Schematic::try_from(input).map_err(|e| e.to_string())?;
+
+    Ok(rule.apply(&schematic).iter().sum())
 }
 
 #[cfg(test)]
@@ -183,9 +441,11 @@ mod tests {
     #[test]
     fn test_parse_schematic() {
         assert_eq!(
-            Schematic::from(&vec!["12.*34", "#5..6$", "+789.."]),
-            Schematic {
-                characters: vec![
+            Schematic::try_from(&["12.*34", "#5..6$", "+789.."][..])
+                .unwrap()
+                .characters,
+            Grid::new(
+                vec![
                     Character::Digit(1),
                     Character::Digit(2),
                     Character::Dot,
@@ -205,57 +465,128 @@ mod tests {
                     Character::Dot,
                     Character::Dot,
                 ],
-                rows: 3,
-                columns: 6,
-            }
+                3,
+                6,
+            )
         );
     }
 
     #[test]
-    fn test_adjacencies() {
-        let schematic = Schematic {
-            characters: vec![Character::Dot; 25],
-            rows: 5,
-            columns: 5,
-        };
-        // North + West boundary checks
-        assert_eq!(schematic.adjacencies(0), vec![1, 5, 6]);
-        // North boundary check
-        assert_eq!(schematic.adjacencies(1), vec![0, 2, 5, 6, 7]);
-        // North + East boundary check
-        assert_eq!(schematic.adjacencies(4), vec![3, 8, 9]);
-        // No boundaries
-        assert_eq!(schematic.adjacencies(8), vec![2, 3, 4, 7, 9, 12, 13, 14]);
-        // South + West boundary checks
-        assert_eq!(schematic.adjacencies(20), vec![15, 16, 21]);
-        // South boundary checks
-        assert_eq!(schematic.adjacencies(21), vec![15, 16, 17, 20, 22]);
-        // South + East boundary checks
-        assert_eq!(schematic.adjacencies(24), vec![18, 19, 23]);
-        // West boundary checks
-        assert_eq!(schematic.adjacencies(5), vec![0, 1, 6, 10, 11]);
-        // East boundary checks
-        assert_eq!(schematic.adjacencies(9), vec![3, 4, 8, 13, 14]);
+    fn test_parse_ragged_schematic() {
+        assert_eq!(
+            Schematic::try_from(&["467..", "...*", "..35."][..]),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 5,
+                found: 4
+            })
+        );
     }
 
     #[test]
-    fn test_part_number_boundaries() {
-        let s = Schematic::from(&vec!["467..114..", "...*......", "..35..633."]);
-        assert_eq!(s.part_number_boundaries(0), (0, 3));
-        assert_eq!(s.part_number_boundaries(1), (0, 3));
-        assert_eq!(s.part_number_boundaries(2), (0, 3));
+    fn test_parse_unicode_schematic() {
+        let s = Schematic::try_from(&["12.", "é✱.", "..3"][..]).unwrap();
+        assert_eq!(s.characters.columns(), 3);
+        assert_eq!(
+            s.characters[Coord::new(1, 0)],
+            Character::Symbol("é".to_string())
+        );
+        assert_eq!(
+            s.characters[Coord::new(1, 1)],
+            Character::Symbol("✱".to_string())
+        );
+        assert_eq!(s.characters[Coord::new(2, 2)], Character::Digit(3));
 
-        assert_eq!(s.part_number_boundaries(22), (22, 24));
-        assert_eq!(s.part_number_boundaries(23), (22, 24));
+        let mut result = part_numbers(&s);
+        result.sort();
+        assert_eq!(result, vec![3, 12]);
+    }
 
-        assert_eq!(s.part_number_boundaries(26), (26, 29));
-        assert_eq!(s.part_number_boundaries(27), (26, 29));
-        assert_eq!(s.part_number_boundaries(28), (26, 29));
+    #[test]
+    fn test_number_spans() {
+        let s = Schematic::try_from(&["467..114..", "...*......", "..35..633."][..]).unwrap();
+        let span = |id, value, row, start, end| NumberSpan {
+            id,
+            value,
+            start: Coord::new(row, start),
+            end,
+        };
+        assert_eq!(
+            s.numbers,
+            vec![
+                span(0, 467, 0, 0, 3),
+                span(1, 114, 0, 5, 8),
+                span(2, 35, 2, 2, 4),
+                span(3, 633, 2, 6, 9),
+            ]
+        );
+        assert_eq!(s.number_ids[Coord::new(0, 2)], Some(0));
+        assert_eq!(s.number_ids[Coord::new(0, 3)], None);
+        assert_eq!(s.number_ids[Coord::new(2, 8)], Some(3));
+        assert_eq!(s.adjacent_numbers(Coord::new(1, 3)), vec![0, 2]);
+
+        // Numbers that run up to the end of a row don't join the next row's
+        let s = Schematic::try_from(&["..12", "34.."][..]).unwrap();
+        assert_eq!(s.numbers, vec![span(0, 12, 0, 2, 4), span(1, 34, 1, 0, 2)]);
+    }
+
+    #[test]
+    fn test_duplicate_part_numbers() {
+        let s = Schematic::try_from(&["12...12", "#.....#", "..12*12"][..]).unwrap();
+        assert_eq!(part_numbers(&s), vec![12, 12, 12, 12]);
+        assert_eq!(gear_ratios(&s), vec![144]);
     }
 
     #[test]
     fn test_part_numbers() {
-        let s = Schematic::from(&vec![
+        let s = Schematic::try_from(
+            &[
+                "467..114..",
+                "...*......",
+                "..35..633.",
+                "......#...",
+                "617*......",
+                ".....+.58.",
+                "..592.....",
+                "......755.",
+                "...$.*....",
+                ".664.598..",
+            ][..],
+        )
+        .unwrap();
+        let mut result = part_numbers(&s);
+        result.sort();
+        let mut expected = vec![467, 35, 633, 617, 592, 755, 664, 598];
+        expected.sort();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_symbol_rule() {
+        assert_eq!(
+            SymbolRule::from_str("part-numbers"),
+            Ok(SymbolRule::part_numbers())
+        );
+        assert_eq!(
+            SymbolRule::from_str("* =2 product"),
+            Ok(SymbolRule::gear_ratios())
+        );
+        assert_eq!(
+            SymbolRule::from_str("#$ <=3 max"),
+            Ok(SymbolRule {
+                symbols: SymbolSet::Only(vec!["#".to_string(), "$".to_string()]),
+                neighbours: NeighbourCount::AtMost(3),
+                aggregation: Aggregation::Max,
+            })
+        );
+        assert!(SymbolRule::from_str("* 2 product").is_err());
+        assert!(SymbolRule::from_str("* =2 mean").is_err());
+        assert!(SymbolRule::from_str("* =2").is_err());
+    }
+
+    #[test]
+    fn test_apply_rule() {
+        let input = [
             "467..114..",
             "...*......",
             "..35..633.",
@@ -266,13 +597,65 @@ mod tests {
             "......755.",
             "...$.*....",
             ".664.598..",
-        ]);
+        ];
+        assert_eq!(apply_rule(&input, "part-numbers"), Ok(4361));
+        assert_eq!(apply_rule(&input, "gears"), Ok(467835));
+        // Every `*` with at least one neighbour: 467 + 35, 617, 755 + 598
+        assert_eq!(apply_rule(&input, "* >=1 sum"), Ok(502 + 617 + 1353));
+        assert_eq!(apply_rule(&input, "* <=1 max"), Ok(617));
+        assert_eq!(apply_rule(&input, "#$ >=1 distinct"), Ok(633 + 664));
+        assert!(apply_rule(&["..", "."], "gears").is_err());
+    }
+
+    #[test]
+    fn test_annotate() {
+        let s = Schematic::try_from(&["12*3..", "....45", "6.<.*."][..]).unwrap();
         assert_eq!(
-            part_numbers(&s).sort(),
-            vec![467, 35, 633, 617, 592, 755, 664, 598].sort()
+            annotate(&s),
+            Grid::new(
+                vec![
+                    Annotation::PartNumber,
+                    Annotation::PartNumber,
+                    Annotation::Gear,
+                    Annotation::PartNumber,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::PartNumber,
+                    Annotation::PartNumber,
+                    Annotation::OtherNumber,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                    Annotation::Plain,
+                ],
+                3,
+                6,
+            )
         );
     }
 
+    #[test]
+    fn test_render() {
+        let input = ["12*3.4", "6.<..."];
+        assert_eq!(
+            render_schematic(&input, RenderFormat::Ansi).unwrap(),
+            "\x1b[32m12\x1b[0m\x1b[1;33m*\x1b[0m\x1b[32m3\x1b[0m.\x1b[31m4\x1b[0m\n\
+             \x1b[31m6\x1b[0m.<..."
+        );
+
+        let html = render_schematic(&input, RenderFormat::Html).unwrap();
+        assert!(html.contains(
+            "<pre>\n<span class=\"part\">12</span><span class=\"gear\">*</span>\
+             <span class=\"part\">3</span>.<span class=\"other\">4</span>\n\
+             <span class=\"other\">6</span>.&lt;...\n</pre>"
+        ));
+    }
+
     #[test]
     fn test_solve_a() {
         let input = vec![
@@ -1,66 +1,281 @@
+use std::cmp;
 use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
 
-struct Scratchcard(usize);
-
-impl From<&str> for Scratchcard {
-    fn from(record: &str) -> Self {
-        let card: Vec<&str> = record.split(":").collect();
-        let sides: Vec<&str> = card[1].split(" | ").collect();
-        let winning_numbers = sides[0]
-            .trim()
-            .split(" ")
-            .into_iter()
-            .filter_map(|s| s.parse::<usize>().ok());
-        let winning_numbers = HashSet::<usize>::from_iter(winning_numbers);
-        let my_numbers: Vec<usize> = sides[1]
-            .trim()
-            .split(" ")
-            .into_iter()
-            .filter_map(|s| s.parse::<usize>().ok())
+#[derive(Debug, PartialEq, Eq)]
+struct Scratchcard {
+    id: usize,
+    winning_numbers: Vec<usize>,
+    numbers: Vec<usize>,
+    /// The numbers you hold that are also winning numbers, in the order you hold them.
+    matched: Vec<usize>,
+}
+
+impl std::str::FromStr for Scratchcard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err_msg = || format!("'{}' is not a valid Scratchcard", s);
+
+        let (card, numbers) = s.split_once(':').ok_or_else(err_msg)?;
+        let id = match card.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["Card", id] => id
+                .parse::<usize>()
+                .map_err(|_| format!("'{}' is not a valid card id", id))?,
+            _ => return Err(err_msg()),
+        };
+
+        let (winning_numbers, numbers) = numbers.split_once('|').ok_or_else(err_msg)?;
+        let parse_numbers = |side: &str| {
+            side.split_whitespace()
+                .map(|n| {
+                    n.parse::<usize>()
+                        .map_err(|_| format!("'{}' is not a valid number", n))
+                })
+                .collect::<Result<Vec<usize>, String>>()
+        };
+        let winning_numbers = parse_numbers(winning_numbers)?;
+        let numbers = parse_numbers(numbers)?;
+
+        let winning = HashSet::<&usize>::from_iter(winning_numbers.iter());
+        let matched = numbers
+            .iter()
+            .filter(|n| winning.contains(n))
+            .copied()
             .collect();
 
-        let mut matches = 0;
-        for number in my_numbers {
-            if winning_numbers.contains(&number) {
-                matches += 1;
-            }
+        Ok(Scratchcard {
+            id,
+            winning_numbers,
+            numbers,
+            matched,
+        })
+    }
+}
+
+impl Scratchcard {
+    fn matches(&self) -> usize {
+        self.matched.len()
+    }
+
+    fn points(&self) -> usize {
+        DoublingScoring.points(self.matches())
+    }
+}
+
+/// How many points a card with `matches` matching numbers is worth.
+pub trait ScoringRule {
+    fn points(&self, matches: usize) -> usize;
+}
+
+/// The puzzle's rule: one point for the first match, doubled for each match after that.
+pub struct DoublingScoring;
+
+impl ScoringRule for DoublingScoring {
+    fn points(&self, matches: usize) -> usize {
+        match matches {
+            0 => 0,
+            matches => 2usize.pow((matches - 1) as u32),
         }
-        Scratchcard(matches)
     }
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    let mut total_points = 0;
-    for record in input {
-        let scratchcard = Scratchcard::from(*record);
-        let matches = scratchcard.0;
-        if matches > 0 {
-            total_points += 2usize.pow((matches - 1) as u32);
+/// One point per match.
+pub struct LinearScoring;
+
+impl ScoringRule for LinearScoring {
+    fn points(&self, matches: usize) -> usize {
+        matches
+    }
+}
+
+/// Which cards a single copy of the card at position `card` wins, out of `card_count` cards.
+/// Positions may repeat if a card wins several copies of the same card.
+pub trait CopyRule {
+    fn cards_won(&self, card: usize, matches: usize, card_count: usize) -> Vec<usize>;
+}
+
+/// The puzzle's rule: one copy of each of the next `matches` cards, stopping at the last card.
+pub struct NextCards;
+
+impl CopyRule for NextCards {
+    fn cards_won(&self, card: usize, matches: usize, card_count: usize) -> Vec<usize> {
+        (card + 1..cmp::min(card + 1 + matches, card_count)).collect()
+    }
+}
+
+/// Like `NextCards`, but never more than the given number of cards.
+pub struct CappedNextCards(pub usize);
+
+impl CopyRule for CappedNextCards {
+    fn cards_won(&self, card: usize, matches: usize, card_count: usize) -> Vec<usize> {
+        NextCards.cards_won(card, cmp::min(matches, self.0), card_count)
+    }
+}
+
+/// One copy of each of the previous `matches` cards, stopping at the first card.
+pub struct PreviousCards;
+
+impl CopyRule for PreviousCards {
+    fn cards_won(&self, card: usize, matches: usize, _card_count: usize) -> Vec<usize> {
+        (card.saturating_sub(matches)..card).collect()
+    }
+}
+
+/// One copy of each of the next `matches` cards, carrying on from the first card after the last.
+pub struct WrappingNextCards;
+
+impl CopyRule for WrappingNextCards {
+    fn cards_won(&self, card: usize, matches: usize, card_count: usize) -> Vec<usize> {
+        (1..=matches).map(|i| (card + i) % card_count).collect()
+    }
+}
+
+fn parse_scratchcards(input: &[&str]) -> Result<Vec<Scratchcard>, String> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, record)| {
+            Scratchcard::from_str(record).map_err(|e| format!("line {}: {}", idx + 1, e))
+        })
+        .collect()
+}
+
+pub fn solve_a(input: &[&str]) -> usize {
+    parse_scratchcards(input)
+        .expect("All Scratchcards should be valid")
+        .iter()
+        .map(|s| s.points())
+        .sum()
+}
+
+/// How many of each card you end up holding under the puzzle's copy rule, given how many
+/// matches each card has. Wins that would reach past the last card are dropped. Copies only flow
+/// forward, so one pass settles every count without going through `copies_held`.
+fn card_copies(matches: &[usize]) -> Vec<usize> {
+    let mut copies = vec![1; matches.len()];
+    for (idx, m) in matches.iter().enumerate() {
+        let end = cmp::min(idx + 1 + m, matches.len());
+        for i in idx + 1..end {
+            copies[i] += copies[idx];
         }
     }
 
-    total_points
+    copies
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    let scratchcards: Vec<Scratchcard> = input
-        .into_iter()
-        .map(|record| Scratchcard::from(*record))
+/// How many of each card you end up holding when every copy of a card wins the cards `rule`
+/// gives it. A card is only scratched once every card that can win it has been, so the counts
+/// settle in a single pass over the cards and their wins. Fails if a card can win copies of
+/// itself, directly or through other cards, since its copies would never stop.
+fn copies_held(matches: &[usize], rule: &dyn CopyRule) -> Result<Vec<usize>, String> {
+    let card_count = matches.len();
+    let wins: Vec<Vec<usize>> = matches
+        .iter()
+        .enumerate()
+        .map(|(idx, m)| rule.cards_won(idx, *m, card_count))
         .collect();
 
-    let mut scratchcard_queue = VecDeque::from_iter(scratchcards.iter().enumerate());
-    let mut total = scratchcard_queue.len();
-    while let Some((idx, scratchcard)) = scratchcard_queue.pop_front() {
-        let matches = scratchcard.0;
-        let start = idx + 1;
-        let end = idx + 1 + matches;
-        for i in start..end {
-            scratchcard_queue.push_back((i, &scratchcards[i]));
+    let mut unscratched_winners = vec![0; card_count];
+    for won in &wins {
+        for card in won {
+            unscratched_winners[*card] += 1;
         }
-        total += matches;
     }
 
-    total
+    let mut copies = vec![1usize; card_count];
+    let mut ready: VecDeque<usize> = (0..card_count)
+        .filter(|idx| unscratched_winners[*idx] == 0)
+        .collect();
+    let mut scratched = 0;
+    while let Some(idx) = ready.pop_front() {
+        scratched += 1;
+        for card in &wins[idx] {
+            copies[*card] = copies[*card]
+                .checked_add(copies[idx])
+                .ok_or_else(|| format!("copies of card {} overflowed", card + 1))?;
+            unscratched_winners[*card] -= 1;
+            if unscratched_winners[*card] == 0 {
+                ready.push_back(*card);
+            }
+        }
+    }
+
+    if scratched < card_count {
+        return Err("copies never settle: some cards win copies of themselves".to_string());
+    }
+
+    Ok(copies)
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    let matches: Vec<usize> = parse_scratchcards(input)
+        .expect("All Scratchcards should be valid")
+        .iter()
+        .map(|s| s.matches())
+        .collect();
+
+    card_copies(&matches).iter().sum()
+}
+
+/// Total points and total cards held when playing `input` with the given rules. Scoring is
+/// `doubling` or `linear`; copies are `next`, `capped:N`, `previous` or `wrapping`.
+pub fn play_variant(input: &[&str], scoring: &str, copies: &str) -> Result<(usize, usize), String> {
+    let scoring: Box<dyn ScoringRule> = match scoring {
+        "doubling" => Box::new(DoublingScoring),
+        "linear" => Box::new(LinearScoring),
+        s => return Err(format!("'{}' is not a valid scoring rule", s)),
+    };
+    let copies: Box<dyn CopyRule> = match copies.split_once(':') {
+        None if copies == "next" => Box::new(NextCards),
+        None if copies == "previous" => Box::new(PreviousCards),
+        None if copies == "wrapping" => Box::new(WrappingNextCards),
+        Some(("capped", cap)) => match cap.parse::<usize>() {
+            Ok(cap) => Box::new(CappedNextCards(cap)),
+            Err(_) => return Err(format!("'{}' is not a valid cap", cap)),
+        },
+        _ => return Err(format!("'{}' is not a valid copy rule", copies)),
+    };
+
+    let matches: Vec<usize> = parse_scratchcards(input)?
+        .iter()
+        .map(|s| s.matches())
+        .collect();
+    let points = matches.iter().map(|m| scoring.points(*m)).sum();
+    let cards = copies_held(&matches, copies.as_ref())?.iter().sum();
+
+    Ok((points, cards))
+}
+
+/// One line per card with its matching numbers, points, how many copies you end up holding and
+/// how many cards those copies win between them.
+pub fn report(input: &[&str]) -> Result<String, String> {
+    let scratchcards = parse_scratchcards(input)?;
+    let matches: Vec<usize> = scratchcards.iter().map(|s| s.matches()).collect();
+    let copies = card_copies(&matches);
+
+    let lines: Vec<String> = scratchcards
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            let matched: Vec<String> = s.matched.iter().map(|n| n.to_string()).collect();
+            let cards_won = NextCards
+                .cards_won(idx, s.matches(), scratchcards.len())
+                .len()
+                * copies[idx];
+            format!(
+                "Card {}: {} matching [{}], {} points, {} copies, wins {} cards",
+                s.id,
+                s.matches(),
+                matched.join(" "),
+                s.points(),
+                copies[idx],
+                cards_won
+            )
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
 }
 
 #[cfg(test)]
@@ -80,6 +295,125 @@ mod tests {
         assert_eq!(solve_a(&input), 13);
     }
 
+    #[test]
+    fn test_parse_scratchcard() {
+        assert_eq!(
+            Scratchcard::from_str("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
+            Ok(Scratchcard {
+                id: 3,
+                winning_numbers: vec![1, 21, 53, 59, 44],
+                numbers: vec![69, 82, 63, 72, 16, 21, 14, 1],
+                matched: vec![21, 1],
+            })
+        );
+        assert!(Scratchcard::from_str("Card 3  1 21 | 69 82").is_err());
+        assert!(Scratchcard::from_str("Card 3: 1 21 69 82").is_err());
+        assert!(Scratchcard::from_str("Card x: 1 21 | 69 82").is_err());
+        assert!(Scratchcard::from_str("Card 3: 1 2l | 69 82").is_err());
+        assert!(Scratchcard::from_str("Crad 3: 1 21 | 69 82").is_err());
+    }
+
+    #[test]
+    fn test_points() {
+        let points = |s| Scratchcard::from_str(s).unwrap().points();
+        assert_eq!(
+            points("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+            8
+        );
+        assert_eq!(
+            points("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
+            2
+        );
+        assert_eq!(
+            points("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+            1
+        );
+        assert_eq!(
+            points("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+            0
+        );
+    }
+
+    #[test]
+    fn test_card_copies() {
+        assert_eq!(card_copies(&[4, 2, 2, 1, 0, 0]), vec![1, 2, 4, 8, 14, 1]);
+
+        // The last cards win copies of cards that don't exist
+        assert_eq!(card_copies(&[1, 3, 5]), vec![1, 2, 3]);
+        assert_eq!(card_copies(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_copy_rules() {
+        assert_eq!(NextCards.cards_won(1, 2, 5), vec![2, 3]);
+        assert_eq!(NextCards.cards_won(3, 2, 5), vec![4]);
+        assert_eq!(CappedNextCards(1).cards_won(1, 2, 5), vec![2]);
+        assert_eq!(PreviousCards.cards_won(3, 2, 5), vec![1, 2]);
+        assert_eq!(PreviousCards.cards_won(1, 2, 5), vec![0]);
+        assert_eq!(WrappingNextCards.cards_won(3, 3, 5), vec![4, 0, 1]);
+    }
+
+    #[test]
+    fn test_copies_held() {
+        // The general settle agrees with the forward pass for the puzzle's rule
+        assert_eq!(
+            copies_held(&[4, 2, 2, 1, 0, 0], &NextCards),
+            Ok(card_copies(&[4, 2, 2, 1, 0, 0]))
+        );
+        assert_eq!(
+            copies_held(&[4, 2, 2, 1, 0, 0], &CappedNextCards(1)),
+            Ok(vec![1, 2, 3, 4, 5, 1])
+        );
+        assert_eq!(
+            copies_held(&[0, 1, 1, 2], &PreviousCards),
+            Ok(vec![5, 4, 2, 1])
+        );
+        // The last card wraps round to the first, which has no matches
+        assert_eq!(
+            copies_held(&[0, 1, 1], &WrappingNextCards),
+            Ok(vec![3, 1, 2])
+        );
+        assert!(copies_held(&[1, 1, 1], &WrappingNextCards).is_err());
+        assert!(copies_held(&[3], &WrappingNextCards).is_err());
+    }
+
+    #[test]
+    fn test_play_variant() {
+        let input = vec![
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+            "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+            "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+            "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+            "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+            "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        ];
+        assert_eq!(play_variant(&input, "doubling", "next"), Ok((13, 30)));
+        assert_eq!(play_variant(&input, "linear", "capped:1"), Ok((9, 16)));
+        assert_eq!(play_variant(&input, "linear", "wrapping"), Ok((9, 30)));
+        assert!(play_variant(&["Card 1: 1 | 1", "Card 2: 2 | 2"], "linear", "wrapping").is_err());
+        assert!(play_variant(&input, "squared", "next").is_err());
+        assert!(play_variant(&input, "linear", "capped:x").is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let input = vec![
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+            "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+            "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        ];
+        assert_eq!(
+            report(&input).unwrap(),
+            "Card 1: 4 matching [83 86 17 48], 8 points, 1 copies, wins 2 cards\n\
+             Card 2: 2 matching [61 32], 2 points, 2 copies, wins 2 cards\n\
+             Card 3: 2 matching [21 1], 2 points, 4 copies, wins 0 cards"
+        );
+        assert_eq!(
+            report(&["Card 1: 41 | 41", "Card 2: 4l | 41"]),
+            Err("line 2: '4l' is not a valid number".to_string())
+        );
+    }
+
     #[test]
     fn test_solve_b() {
         let input = vec![
@@ -1,291 +1,511 @@
-use std::cmp;
 use std::collections::HashMap;
+use std::str::FromStr;
 
-#[derive(Debug)]
-struct MapRange {
-    src_start: usize,
-    dest_offset: isize,
-    length: usize,
-}
+use crate::interval::{IntervalSet, PiecewiseMap};
 
 struct Map {
     src_type: String,
-    ranges: Vec<MapRange>,
+    dest_type: String,
+    mapping: PiecewiseMap,
 }
 
-impl Map {
-    fn range_overlap(&self, from: &MapRange) -> Vec<MapRange> {
-        let mut results = Vec::<MapRange>::new();
-        for r in &self.ranges {
-            let overlap_start = cmp::max(from.src_start, r.src_start);
-            let overlap_end = overlap_start
-                + cmp::min(from.src_start + from.length, r.src_start + r.length)
-                - cmp::max(from.src_start, r.src_start);
-
-            if overlap_end > overlap_start {
-                results.push(MapRange {
-                    src_start: overlap_start,
-                    dest_offset: r.dest_offset,
-                    length: overlap_end - overlap_start,
-                })
+/// Every map in the almanac, keyed by the category it converts from. Each category converts to
+/// at most one other, so the maps form a graph where any two categories are joined by at most
+/// one chain.
+struct Almanac {
+    maps: HashMap<String, Map>,
+}
+
+impl Almanac {
+    /// Parses every map after the `seeds:` line.
+    fn new(input: &[&str]) -> Result<Self, String> {
+        // The seeds line ends a block just like a blank line, in case a map follows it directly
+        let mut delimiter_indices = vec![0];
+        delimiter_indices.extend(
+            input
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(_, s)| s.is_empty())
+                .map(|(idx, _)| idx),
+        );
+        delimiter_indices.push(input.len());
+
+        let mut maps = HashMap::<String, Map>::new();
+        for indices in delimiter_indices.windows(2) {
+            let start = indices[0] + 1;
+            if start >= indices[1] {
+                // Extra blank lines between or after maps
+                continue;
             }
-        }
-        results.sort_by_key(|r| r.src_start);
-
-        // Fill in gaps with 1:1 mappings
-        let mut direct_map_ranges = Vec::<MapRange>::new();
-        let mut previous_end = from.src_start;
-        for r in &results {
-            if r.src_start >= previous_end {
-                let length = r.src_start - previous_end;
-                if length >= 1 {
-                    direct_map_ranges.push(MapRange {
-                        src_start: previous_end,
-                        dest_offset: 0,
-                        length: r.src_start - previous_end,
-                    });
-                }
+            let map = parse_map(&input[start..indices[1]])
+                .map_err(|(idx, e)| format!("line {}: {}", start + idx + 1, e))?;
+            if maps.contains_key(&map.src_type) {
+                return Err(format!(
+                    "line {}: there is already a map from '{}'",
+                    start + 1,
+                    map.src_type
+                ));
             }
-            previous_end = r.src_start + r.length + 1;
+            maps.insert(map.src_type.to_owned(), map);
         }
-        let from_end = (from.src_start + from.length) as isize;
-        let remaining = from_end - (previous_end as isize);
-        if remaining > 0 {
-            direct_map_ranges.push(MapRange {
-                src_start: previous_end,
-                dest_offset: 0,
-                length: remaining as usize,
-            });
+
+        Ok(Almanac { maps })
+    }
+
+    /// The maps to apply, in order, to convert `from` into `to`.
+    fn path(&self, from: &str, to: &str) -> Result<Vec<&Map>, String> {
+        let mut path = Vec::<&Map>::new();
+        let mut current = from;
+        while current != to {
+            let map = self
+                .maps
+                .get(current)
+                .ok_or_else(|| format!("no chain of maps from '{}' to '{}'", from, to))?;
+            if path.iter().any(|m| m.src_type == map.src_type) {
+                let categories: Vec<&str> = path
+                    .iter()
+                    .map(|m| m.src_type.as_str())
+                    .chain([map.src_type.as_str()])
+                    .collect();
+                return Err(format!(
+                    "maps from '{}' loop without reaching '{}': {}",
+                    from,
+                    to,
+                    categories.join(" -> ")
+                ));
+            }
+
+            path.push(map);
+            current = &map.dest_type;
         }
 
-        results.append(&mut direct_map_ranges);
+        Ok(path)
+    }
 
-        // Shift results by offset
-        results
-            .iter()
-            .map(|r| MapRange {
-                src_start: (r.src_start as isize + r.dest_offset) as usize,
-                dest_offset: 0,
-                length: r.length,
-            })
-            .collect()
+    /// Every map on the path from `from` to `to` composed into one.
+    fn conversion(&self, from: &str, to: &str) -> Result<PiecewiseMap, String> {
+        Ok(compose(&self.path(from, to)?))
     }
 
-    fn ranges_overlap(&self, from_ranges: Vec<MapRange>) -> Vec<MapRange> {
-        from_ranges
+    /// Every `from` value that ends up in `set` once converted to `to`.
+    fn preimage(&self, from: &str, to: &str, set: &IntervalSet) -> Result<IntervalSet, String> {
+        Ok(self
+            .path(from, to)?
             .iter()
-            .map(|f| self.range_overlap(f))
-            .flatten()
-            .collect()
+            .rev()
+            .fold(set.clone(), |set, map| map.mapping.preimage(&set)))
+    }
+
+    fn seed_to_location(&self) -> Result<PiecewiseMap, String> {
+        self.conversion("seed", "location")
     }
 }
 
-fn parse_map(record: &[&str]) -> Map {
-    let types: Vec<&str> = record[0].split(" ").next().unwrap().split("-").collect();
-    let src_type = types[0].to_string();
+/// Maps applied one after another, as a single map.
+fn compose(path: &[&Map]) -> PiecewiseMap {
+    path.iter().fold(PiecewiseMap::default(), |composed, map| {
+        composed.compose(&map.mapping)
+    })
+}
 
-    let ranges = record[1..]
-        .iter()
-        .map(|s| {
-            let x: Vec<usize> = s
-                .split(" ")
-                .filter_map(|x| x.parse::<usize>().ok())
-                .collect();
-            MapRange {
-                src_start: x[1],
-                dest_offset: (x[0] as isize) - (x[1] as isize),
-                length: x[2],
+/// Parses an `X-to-Y map:` header and its ranges. Errors carry the index of the offending line.
+fn parse_map(record: &[&str]) -> Result<Map, (usize, String)> {
+    let header = *record.first().ok_or((0, "empty map block".to_string()))?;
+    let (src_type, dest_type) = header
+        .strip_suffix(" map:")
+        .and_then(|types| types.split_once("-to-"))
+        .filter(|(src, dest)| !src.is_empty() && !dest.is_empty())
+        .ok_or_else(|| (0, format!("'{}' is not a valid map header", header)))?;
+
+    let mut pieces = Vec::<(std::ops::Range<usize>, isize)>::new();
+    for (idx, line) in record.iter().enumerate().skip(1) {
+        let x: Vec<usize> = line
+            .split(" ")
+            .map(|x| x.parse::<usize>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| (idx, format!("'{}' is not a valid map range", line)))?;
+        if x.len() != 3 {
+            return Err((idx, format!("'{}' is not a valid map range", line)));
+        }
+        pieces.push((x[1]..x[1] + x[2], (x[0] as isize) - (x[1] as isize)));
+    }
+
+    let mapping = PiecewiseMap::try_new(pieces.clone()).map_err(|(first, second)| {
+        // Report the later of the two lines, as the one that overlaps an earlier range
+        let idx = pieces
+            .iter()
+            .rposition(|(r, _)| *r == first || *r == second)
+            .unwrap();
+        let other = if pieces[idx].0 == first {
+            second
+        } else {
+            first
+        };
+        (
+            idx + 1,
+            format!("range {:?} overlaps {:?}", pieces[idx].0, other),
+        )
+    })?;
+
+    Ok(Map {
+        src_type: src_type.to_string(),
+        dest_type: dest_type.to_string(),
+        mapping,
+    })
+}
+
+/// How to read the seeds the almanac asks about.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SeedSpec {
+    /// Every number on the `seeds:` line is a seed.
+    Values,
+    /// The `seeds:` line is pairs of range start and length.
+    Pairs,
+    /// Ignore the `seeds:` line and use these seeds instead.
+    Ranges(IntervalSet),
+}
+
+impl SeedSpec {
+    fn seeds(&self, line: &str) -> Result<IntervalSet, String> {
+        if let SeedSpec::Ranges(ranges) = self {
+            return Ok(ranges.clone());
+        }
+
+        let values: Vec<usize> = line
+            .strip_prefix("seeds:")
+            .ok_or_else(|| format!("'{}' is not a valid seeds line", line))?
+            .split_whitespace()
+            .map(|x| {
+                x.parse::<usize>()
+                    .map_err(|_| format!("'{}' is not a valid seed", x))
+            })
+            .collect::<Result<_, _>>()?;
+
+        match self {
+            SeedSpec::Pairs if values.len() % 2 != 0 => {
+                Err(format!("'{}' has a seed range with no length", line))
             }
-        })
-        .collect();
+            SeedSpec::Pairs => Ok(values.chunks(2).map(|c| c[0]..c[0] + c[1]).collect()),
+            _ => Ok(values.into_iter().map(|x| x..x + 1).collect()),
+        }
+    }
+}
 
-    Map { src_type, ranges }
+impl FromStr for SeedSpec {
+    type Err = String;
+
+    /// `values`, `pairs`, or comma separated ranges such as `79..93,55..68`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "values" => Ok(SeedSpec::Values),
+            "pairs" => Ok(SeedSpec::Pairs),
+            _ => s
+                .split(',')
+                .map(|range| match range.split_once("..") {
+                    Some((start, end)) => match (start.parse(), end.parse()) {
+                        (Ok(start), Ok(end)) => Ok(start..end),
+                        _ => Err(format!("'{}' is not a valid seed spec", s)),
+                    },
+                    None => Err(format!("'{}' is not a valid seed spec", s)),
+                })
+                .collect::<Result<IntervalSet, String>>()
+                .map(SeedSpec::Ranges),
+        }
+    }
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    let seed_ranges: Vec<MapRange> = input[0]
-        .split(" ")
-        .skip(1)
-        .filter_map(|s| s.parse::<usize>().ok())
-        .map(|x| MapRange {
-            src_start: x,
-            dest_offset: 0,
-            length: 1,
-        })
-        .collect();
+/// Every location the seeds in `spec` end up at. `min`, `max`, `len` and `ranges` on the result
+/// answer the usual questions.
+pub fn locations(input: &[&str], spec: &SeedSpec) -> Result<IntervalSet, String> {
+    let seeds = spec
+        .seeds(input.first().copied().unwrap_or_default())
+        .map_err(|e| format!("line 1: {}", e))?;
 
-    let mut delimiter_indices = input
-        .iter()
-        .enumerate()
-        .skip(1)
-        .filter(|(_, s)| **s == "")
-        .map(|(idx, _)| idx.clone())
-        .collect::<Vec<usize>>();
-    delimiter_indices.push(input.len());
-
-    let maps_iter = delimiter_indices
-        .windows(2)
-        .map(|indices| &input[(indices[0] + 1)..indices[1]])
-        .map(|record| parse_map(record));
-    let maps = HashMap::<String, Map>::from_iter(maps_iter.map(|m| (m.src_type.to_owned(), m)));
-
-    let seed_soil = &maps["seed"];
-    let soil_fertilizer = &maps["soil"];
-    let fertilizer_water = &maps["fertilizer"];
-    let water_light = &maps["water"];
-    let light_temperature = &maps["light"];
-    let temperature_humidity = &maps["temperature"];
-    let humidity_location = &maps["humidity"];
-
-    let mut ranges = seed_soil.ranges_overlap(seed_ranges);
-    ranges = soil_fertilizer.ranges_overlap(ranges);
-    ranges = fertilizer_water.ranges_overlap(ranges);
-    ranges = water_light.ranges_overlap(ranges);
-    ranges = light_temperature.ranges_overlap(ranges);
-    ranges = temperature_humidity.ranges_overlap(ranges);
-    ranges = humidity_location.ranges_overlap(ranges);
-
-    ranges
-        .into_iter()
-        .min_by_key(|r| r.src_start)
-        .unwrap()
-        .src_start
+    Ok(Almanac::new(input)?.seed_to_location()?.apply_set(&seeds))
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    let seed_values: Vec<usize> = input[0]
-        .split(" ")
-        .skip(1)
-        .filter_map(|s| s.parse::<usize>().ok())
-        .collect();
-    let seed_ranges: Vec<MapRange> = seed_values
-        .chunks(2)
-        .map(|c| MapRange {
-            src_start: c[0],
-            dest_offset: 0,
-            length: c[1],
-        })
+pub fn solve_a(input: &[&str]) -> usize {
+    locations(input, &SeedSpec::Values).unwrap().min().unwrap()
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    locations(input, &SeedSpec::Pairs).unwrap().min().unwrap()
+}
+
+/// Nearest, furthest and number of locations for `spec`, followed by every location range.
+pub fn location_report(input: &[&str], spec: &SeedSpec) -> Result<String, String> {
+    let locations = locations(input, spec)?;
+    let (Some(min), Some(max)) = (locations.min(), locations.max()) else {
+        return Ok("no seeds".to_string());
+    };
+
+    let mut lines = vec![format!(
+        "min {}, max {}, count {}",
+        min,
+        max,
+        locations.len()
+    )];
+    lines.extend(
+        locations
+            .ranges()
+            .iter()
+            .map(|r| format!("{}..{}", r.start, r.end)),
+    );
+
+    Ok(lines.join("\n"))
+}
+
+/// The chain of maps from `from` to `to` as one map, one piece per line.
+pub fn conversion(input: &[&str], from: &str, to: &str) -> Result<String, String> {
+    let almanac = Almanac::new(input)?;
+    let path = almanac.path(from, to)?;
+    let categories: Vec<&str> = path
+        .iter()
+        .map(|m| m.src_type.as_str())
+        .chain([to])
         .collect();
+    let mut lines = vec![categories.join(" -> ")];
+    lines.extend(compose(&path).to_string().lines().map(String::from));
+
+    Ok(lines.join("\n"))
+}
 
-    let mut delimiter_indices = input
+/// The `from` ranges that convert into `targets`, one range per line.
+pub fn preimage(
+    input: &[&str],
+    from: &str,
+    to: &str,
+    targets: IntervalSet,
+) -> Result<String, String> {
+    let seeds = Almanac::new(input)?.preimage(from, to, &targets)?;
+    let lines: Vec<String> = seeds
+        .ranges()
         .iter()
-        .enumerate()
-        .skip(1)
-        .filter(|(_, s)| **s == "")
-        .map(|(idx, _)| idx.clone())
-        .collect::<Vec<usize>>();
-    delimiter_indices.push(input.len());
-
-    let maps_iter = delimiter_indices
-        .windows(2)
-        .map(|indices| &input[(indices[0] + 1)..indices[1]])
-        .map(|record| parse_map(record));
-    let maps = HashMap::<String, Map>::from_iter(maps_iter.map(|m| (m.src_type.to_owned(), m)));
-
-    let seed_soil = &maps["seed"];
-    let soil_fertilizer = &maps["soil"];
-    let fertilizer_water = &maps["fertilizer"];
-    let water_light = &maps["water"];
-    let light_temperature = &maps["light"];
-    let temperature_humidity = &maps["temperature"];
-    let humidity_location = &maps["humidity"];
-
-    let mut ranges = seed_soil.ranges_overlap(seed_ranges);
-    ranges = soil_fertilizer.ranges_overlap(ranges);
-    ranges = fertilizer_water.ranges_overlap(ranges);
-    ranges = water_light.ranges_overlap(ranges);
-    ranges = light_temperature.ranges_overlap(ranges);
-    ranges = temperature_humidity.ranges_overlap(ranges);
-    ranges = humidity_location.ranges_overlap(ranges);
-
-    ranges
-        .into_iter()
-        .min_by_key(|r| r.src_start)
-        .unwrap()
-        .src_start
+        .map(|r| format!("{}..{}", r.start, r.end))
+        .collect();
+
+    Ok(lines.join("\n"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const SAMPLE: [&str; 33] = [
+        "seeds: 79 14 55 13",
+        "",
+        "seed-to-soil map:",
+        "50 98 2",
+        "52 50 48",
+        "",
+        "soil-to-fertilizer map:",
+        "0 15 37",
+        "37 52 2",
+        "39 0 15",
+        "",
+        "fertilizer-to-water map:",
+        "49 53 8",
+        "0 11 42",
+        "42 0 7",
+        "57 7 4",
+        "",
+        "water-to-light map:",
+        "88 18 7",
+        "18 25 70",
+        "",
+        "light-to-temperature map:",
+        "45 77 23",
+        "81 45 19",
+        "68 64 13",
+        "",
+        "temperature-to-humidity map:",
+        "0 69 1",
+        "1 0 69",
+        "",
+        "humidity-to-location map:",
+        "60 56 37",
+        "56 93 4",
+    ];
+
     #[test]
-    fn test_solve_a() {
-        let input = vec![
-            "seeds: 79 14 55 13",
-            "",
-            "seed-to-soil map:",
-            "50 98 2",
-            "52 50 48",
-            "",
-            "soil-to-fertilizer map:",
-            "0 15 37",
-            "37 52 2",
-            "39 0 15",
-            "",
-            "fertilizer-to-water map:",
-            "49 53 8",
-            "0 11 42",
-            "42 0 7",
-            "57 7 4",
-            "",
-            "water-to-light map:",
-            "88 18 7",
-            "18 25 70",
-            "",
-            "light-to-temperature map:",
-            "45 77 23",
-            "81 45 19",
-            "68 64 13",
+    fn test_seed_to_location() {
+        let almanac = Almanac::new(&SAMPLE).unwrap();
+        let composed = almanac.seed_to_location().unwrap();
+        assert_eq!(composed.apply(79), 82);
+        assert_eq!(composed.apply(14), 43);
+        assert_eq!(composed.apply(55), 86);
+        assert_eq!(composed.apply(13), 35);
+    }
+
+    #[test]
+    fn test_path() {
+        let almanac = Almanac::new(&SAMPLE).unwrap();
+        let path: Vec<&str> = almanac
+            .path("fertilizer", "humidity")
+            .unwrap()
+            .iter()
+            .map(|m| m.dest_type.as_str())
+            .collect();
+        assert_eq!(path, vec!["water", "light", "temperature", "humidity"]);
+        assert_eq!(almanac.path("soil", "soil").unwrap().len(), 0);
+        assert_eq!(
+            almanac.path("humidity", "seed").err(),
+            Some("no chain of maps from 'humidity' to 'seed'".to_string())
+        );
+
+        let text = conversion(&SAMPLE, "water", "light").unwrap();
+        assert_eq!(text, "water -> light\n18..25 -> 88..95\n25..95 -> 18..88");
+    }
+
+    #[test]
+    fn test_preimage() {
+        let almanac = Almanac::new(&SAMPLE).unwrap();
+        let forward = almanac.seed_to_location().unwrap();
+        let locations: IntervalSet = [35..36, 46..47, 60..70].into_iter().collect();
+        let seeds = almanac.preimage("seed", "location", &locations).unwrap();
+        assert!(seeds.contains(13));
+        assert!(seeds.contains(82));
+        assert!(!seeds.contains(79));
+
+        // Round trip: the seeds map back inside the locations, and no other seed does
+        assert!(forward.apply_set(&seeds).difference(&locations).is_empty());
+        for seed in 0..200 {
+            assert_eq!(
+                seeds.contains(seed),
+                locations.contains(forward.apply(seed))
+            );
+        }
+
+        // Running the whole input backwards from every location gives back every seed
+        let everything: IntervalSet = std::iter::once(0..200).collect();
+        let all_seeds = almanac.preimage("seed", "location", &everything).unwrap();
+        assert!(everything.difference(&all_seeds).is_empty());
+
+        assert_eq!(
+            preimage(&SAMPLE, "humidity", "location", locations).unwrap(),
+            "35..36\n46..47\n56..66"
+        );
+    }
+
+    #[test]
+    fn test_cyclic_chain() {
+        let input = [
+            "seeds: 1",
             "",
-            "temperature-to-humidity map:",
-            "0 69 1",
-            "1 0 69",
+            "a-to-b map:",
+            "0 1 1",
             "",
-            "humidity-to-location map:",
-            "60 56 37",
-            "56 93 4",
+            "b-to-a map:",
+            "1 0 1",
         ];
-        assert_eq!(solve_a(&input), 35);
+        let almanac = Almanac::new(&input).unwrap();
+        assert_eq!(
+            almanac.path("a", "c").err(),
+            Some("maps from 'a' loop without reaching 'c': a -> b -> a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extra_blank_lines() {
+        let mut input = SAMPLE.to_vec();
+        input.insert(5, "");
+        input.push("");
+        input.push("");
+        let almanac = Almanac::new(&input).unwrap();
+        assert_eq!(almanac.maps.len(), 7);
+        assert_eq!(
+            locations(&input, &SeedSpec::Values).unwrap().min(),
+            Some(35)
+        );
+        assert_eq!(
+            parse_map(&[]).err(),
+            Some((0, "empty map block".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_map_right_after_seeds() {
+        let mut input = SAMPLE.to_vec();
+        input.remove(1);
+        let almanac = Almanac::new(&input).unwrap();
+        assert_eq!(almanac.maps.len(), 7);
+        assert_eq!(
+            locations(&input, &SeedSpec::Values).unwrap().min(),
+            Some(35)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |input: &[&str]| Almanac::new(input).err().unwrap();
+        assert_eq!(
+            err(&["seeds: 1", "", "seed to soil:", "1 2 3"]),
+            "line 3: 'seed to soil:' is not a valid map header"
+        );
+        assert_eq!(
+            err(&["seeds: 1", "", "seed-to-soil map:", "1 2 3", "4 5"]),
+            "line 5: '4 5' is not a valid map range"
+        );
+        assert_eq!(
+            err(&["seeds: 1", "", "seed-to-soil map:", "50 98 2", "1 97 3"]),
+            "line 5: range 97..100 overlaps 98..100"
+        );
+        assert_eq!(
+            err(&["seeds: 1", "", "a-to-b map:", "", "a-to-c map:"]),
+            "line 5: there is already a map from 'a'"
+        );
+    }
+
+    #[test]
+    fn test_ranges_overlap_fills_gaps() {
+        let map = parse_map(&["seed-to-soil map:", "100 2 2"]).unwrap();
+        let seeds: IntervalSet = std::iter::once(0..10).collect();
+        // 4 sits straight after the mapped range and maps to itself
+        assert_eq!(
+            map.mapping.apply_set(&seeds).ranges(),
+            &[0..2, 4..10, 100..102]
+        );
+    }
+
+    #[test]
+    fn test_seed_specs() {
+        let query = |spec: &str| locations(&SAMPLE, &spec.parse().unwrap()).unwrap();
+        let values = query("values");
+        assert_eq!(values.ranges(), &[35..36, 43..44, 82..83, 86..87]);
+        assert_eq!(
+            (values.min(), values.max(), values.len()),
+            (Some(35), Some(86), 4)
+        );
+
+        let pairs = query("pairs");
+        assert_eq!((pairs.min(), pairs.len()), (Some(46), 27));
+        assert_eq!(query("79..93,55..68"), pairs);
+        assert_eq!(query("82..83").min(), Some(46));
+
+        assert_eq!(
+            "seeds".parse::<SeedSpec>(),
+            Err("'seeds' is not a valid seed spec".to_string())
+        );
+        let mut odd = SAMPLE;
+        odd[0] = "seeds: 79 14 55";
+        assert_eq!(
+            locations(&odd, &SeedSpec::Pairs),
+            Err("line 1: 'seeds: 79 14 55' has a seed range with no length".to_string())
+        );
+        assert_eq!(
+            location_report(&SAMPLE, &SeedSpec::Values).unwrap(),
+            "min 35, max 86, count 4\n35..36\n43..44\n82..83\n86..87"
+        );
+    }
+
+    #[test]
+    fn test_solve_a() {
+        assert_eq!(solve_a(&SAMPLE), 35);
     }
 
     #[test]
     fn test_solve_b() {
-        let input = vec![
-            "seeds: 79 14 55 13",
-            "",
-            "seed-to-soil map:",
-            "50 98 2",
-            "52 50 48",
-            "",
-            "soil-to-fertilizer map:",
-            "0 15 37",
-            "37 52 2",
-            "39 0 15",
-            "",
-            "fertilizer-to-water map:",
-            "49 53 8",
-            "0 11 42",
-            "42 0 7",
-            "57 7 4",
-            "",
-            "water-to-light map:",
-            "88 18 7",
-            "18 25 70",
-            "",
-            "light-to-temperature map:",
-            "45 77 23",
-            "81 45 19",
-            "68 64 13",
-            "",
-            "temperature-to-humidity map:",
-            "0 69 1",
-            "1 0 69",
-            "",
-            "humidity-to-location map:",
-            "60 56 37",
-            "56 93 4",
-        ];
-        assert_eq!(solve_b(&input), 46);
+        assert_eq!(solve_b(&SAMPLE), 46);
     }
 }
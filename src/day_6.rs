@@ -1,66 +1,370 @@
-fn ways(total_time: usize, distance_record: usize) -> Vec<usize> {
-    let mut results = Vec::<usize>::new();
-    for time_held in 1..total_time {
-        let distance = time_held * (total_time - time_held);
-        if distance > distance_record {
-            results.push(time_held);
+use std::ops::Range;
+
+/// Every hold time that beats `distance_record`, found by solving `t * (total_time - t) > D`
+/// directly. Winning times are symmetric about `total_time / 2`, so only the lower edge has to
+/// be found. Squares are taken in `u128` so even `usize::MAX` races can't overflow.
+fn winning_holds(total_time: usize, distance_record: usize) -> Range<usize> {
+    let (time, record) = (total_time as u128, distance_record as u128);
+    let beats = |held: u128| held * (time - held) > record;
+
+    let Some(discriminant) = (time * time).checked_sub(4 * record) else {
+        return 0..0;
+    };
+    // isqrt rounds down, so this lands on the lower root or at most one below a winning time
+    let mut lowest = (time - discriminant.isqrt()) / 2;
+    while lowest <= time / 2 && !beats(lowest) {
+        lowest += 1;
+    }
+
+    if lowest > time / 2 {
+        0..0
+    } else {
+        lowest as usize..(time - lowest + 1) as usize
+    }
+}
+
+fn ways(total_time: usize, distance_record: usize) -> usize {
+    winning_holds(total_time, distance_record).len()
+}
+
+/// How holding the button turns into distance. The puzzle's boat is `BoatModel::STANDARD`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoatModel {
+    /// Speed gained for each millisecond the button is held.
+    pub acceleration: usize,
+    /// Speed the boat can't go beyond, however long the button is held.
+    pub top_speed: Option<usize>,
+    /// Speed lost each millisecond once the boat is moving. The boat never goes backwards.
+    pub drag: usize,
+}
+
+impl BoatModel {
+    pub const STANDARD: BoatModel = BoatModel {
+        acceleration: 1,
+        top_speed: None,
+        drag: 0,
+    };
+
+    /// How far the boat goes when the button is held for `time_held` of `total_time`.
+    pub fn distance(&self, time_held: usize, total_time: usize) -> u128 {
+        let mut speed = time_held as u128 * self.acceleration as u128;
+        if let Some(top_speed) = self.top_speed {
+            speed = speed.min(top_speed as u128);
+        }
+        let moving = total_time.saturating_sub(time_held) as u128;
+        if self.drag == 0 {
+            return speed * moving;
+        }
+
+        // Speed drops by `drag` each millisecond, so the distance is an arithmetic series over the
+        // milliseconds the boat is still moving
+        let drag = self.drag as u128;
+        let steps = moving.min(speed.div_ceil(drag));
+        steps * speed - drag * steps * steps.saturating_sub(1) / 2
+    }
+
+    /// The number of hold times that beat `distance_record`.
+    pub fn ways(&self, total_time: usize, distance_record: usize) -> usize {
+        if self.top_speed.is_none() && self.drag == 0 {
+            if self.acceleration == 0 {
+                return 0;
+            }
+            // `a * t * (T - t) > D` exactly when `t * (T - t) > D / a`
+            return ways(total_time, distance_record / self.acceleration);
         }
+
+        // Caps and drag have no neat closed form, so check every hold time
+        (1..total_time)
+            .filter(|held| self.distance(*held, total_time) > distance_record as u128)
+            .count()
     }
+}
 
-    results
+/// How to read the numbers on a race sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kerning {
+    /// Each number is its own race.
+    Columns,
+    /// The spaces are bad kerning, so each row is one number.
+    Merged,
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    let times = input[0]
-        .split(":")
-        .nth(1)
-        .unwrap()
-        .trim()
-        .split(" ")
-        .filter_map(|s| s.parse::<usize>().ok());
-    let distances = input[1]
-        .split(":")
-        .nth(1)
-        .unwrap()
-        .trim()
-        .split(" ")
-        .filter_map(|s| s.parse::<usize>().ok());
-
-    times
-        .zip(distances)
-        .map(|record| ways(record.0, record.1))
-        .map(|ways| ways.len())
-        .product()
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Race {
+    pub time: usize,
+    pub distance: usize,
+}
+
+/// A `Time:` row and a `Distance:` row, in either order, read into races.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RaceSheet {
+    pub races: Vec<Race>,
+}
+
+impl RaceSheet {
+    pub fn parse(input: &[&str], kerning: Kerning) -> Result<Self, String> {
+        let mut times: Option<Vec<usize>> = None;
+        let mut distances: Option<Vec<usize>> = None;
+        for (idx, line) in input.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let err = |e: String| format!("line {}: {}", idx + 1, e);
+            let (label, values) = line
+                .split_once(':')
+                .ok_or_else(|| err(format!("'{}' has no label", line)))?;
+            let row = match label.trim() {
+                "Time" => &mut times,
+                "Distance" => &mut distances,
+                label => return Err(err(format!("'{}' is not a valid row label", label))),
+            };
+            if row.is_some() {
+                return Err(err(format!("there is already a {} row", label.trim())));
+            }
+            *row = Some(parse_row(values, kerning).map_err(err)?);
+        }
+
+        let times = times.ok_or("there is no Time row")?;
+        let distances = distances.ok_or("there is no Distance row")?;
+        if times.len() != distances.len() {
+            return Err(format!(
+                "there are {} times but {} distances",
+                times.len(),
+                distances.len()
+            ));
+        }
+
+        Ok(RaceSheet {
+            races: times
+                .into_iter()
+                .zip(distances)
+                .map(|(time, distance)| Race { time, distance })
+                .collect(),
+        })
+    }
+
+    /// The product of the number of ways to win each race.
+    pub fn ways(&self, model: &BoatModel) -> usize {
+        self.races
+            .iter()
+            .map(|race| model.ways(race.time, race.distance))
+            .product()
+    }
+}
+
+fn parse_row(values: &str, kerning: Kerning) -> Result<Vec<usize>, String> {
+    let columns: Vec<&str> = values.split_whitespace().collect();
+    let columns = match kerning {
+        Kerning::Columns => columns,
+        Kerning::Merged if columns.is_empty() => columns,
+        Kerning::Merged => vec![values.trim()],
+    };
+
+    columns
+        .into_iter()
+        .map(|column| {
+            let digits: String = column.split_whitespace().collect();
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("'{}' is not a valid number", column));
+            }
+            digits
+                .parse::<usize>()
+                .map_err(|_| format!("'{}' is too large", column))
+        })
+        .collect()
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    let time = input[0]
-        .split(":")
-        .nth(1)
-        .unwrap()
-        .trim()
-        .split(" ")
-        .filter_map(|s| s.parse::<usize>().ok())
-        .fold("".to_string(), |acc, i| acc + &i.to_string())
-        .parse::<usize>()
-        .unwrap();
-    let distance = input[1]
-        .split(":")
-        .nth(1)
-        .unwrap()
-        .trim()
-        .split(" ")
-        .fold("".to_string(), |acc, i| acc + &i.to_string())
-        .parse::<usize>()
-        .unwrap();
-
-    ways(time, distance).iter().count()
+pub fn solve_a(input: &[&str]) -> usize {
+    solve_with(input, &BoatModel::STANDARD).unwrap().0
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    solve_with(input, &BoatModel::STANDARD).unwrap().1
+}
+
+/// Part a and part b answers for a different boat.
+pub fn solve_with(input: &[&str], model: &BoatModel) -> Result<(usize, usize), String> {
+    Ok((
+        RaceSheet::parse(input, Kerning::Columns)?.ways(model),
+        RaceSheet::parse(input, Kerning::Merged)?.ways(model),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn brute_force(total_time: usize, distance_record: usize) -> Vec<usize> {
+        (1..total_time)
+            .filter(|held| held * (total_time - held) > distance_record)
+            .collect()
+    }
+
+    #[test]
+    fn test_winning_holds() {
+        assert_eq!(winning_holds(7, 9), 2..6);
+        assert_eq!(winning_holds(30, 200), 11..20);
+        // Exactly matching the record isn't a win
+        assert_eq!(winning_holds(4, 4), 0..0);
+        assert_eq!(winning_holds(0, 0), 0..0);
+        for total_time in 0..60 {
+            for distance_record in 0..1000 {
+                let holds: Vec<usize> = winning_holds(total_time, distance_record).collect();
+                assert_eq!(holds, brute_force(total_time, distance_record));
+            }
+        }
+    }
+
+    #[test]
+    fn test_winning_holds_huge() {
+        let holds = winning_holds(usize::MAX, 0);
+        assert_eq!(holds, 1..usize::MAX);
+        assert_eq!(winning_holds(usize::MAX, usize::MAX), 2..usize::MAX - 1);
+        // Only the midpoint reaches 2^64, one more than the record
+        assert_eq!(winning_holds(1 << 33, usize::MAX), 1 << 32..(1 << 32) + 1);
+        assert_eq!(winning_holds(1 << 32, usize::MAX), 0..0);
+    }
+
+    #[test]
+    fn test_boat_model_distance() {
+        let standard = BoatModel::STANDARD;
+        assert_eq!(standard.distance(3, 7), 12);
+        assert_eq!(standard.distance(7, 7), 0);
+
+        let capped = BoatModel {
+            top_speed: Some(2),
+            ..standard
+        };
+        assert_eq!(capped.distance(3, 7), 8);
+
+        let dragged = BoatModel {
+            drag: 2,
+            ..standard
+        };
+        // Speeds 5, 3, 1 then stopped
+        assert_eq!(dragged.distance(5, 10), 9);
+        // Speeds 5, 3 before the race ends
+        assert_eq!(dragged.distance(5, 7), 8);
+        assert_eq!(dragged.distance(0, 7), 0);
+    }
+
+    #[test]
+    fn test_boat_model_ways() {
+        let standard = BoatModel::STANDARD;
+        let double = BoatModel {
+            acceleration: 2,
+            ..standard
+        };
+        for model in [standard, double] {
+            for total_time in 0..40 {
+                for distance_record in 0..300 {
+                    let expected = (0..=total_time)
+                        .filter(|held| model.distance(*held, total_time) > distance_record)
+                        .count();
+                    assert_eq!(
+                        model.ways(total_time, distance_record as usize),
+                        expected,
+                        "{:?} {} {}",
+                        model,
+                        total_time,
+                        distance_record
+                    );
+                }
+            }
+        }
+
+        // Holding 1..=6 goes 12, 20, 16, 12, 8, 4
+        let capped = BoatModel {
+            top_speed: Some(4),
+            ..double
+        };
+        assert_eq!(capped.ways(7, 10), 4);
+        assert_eq!(capped.ways(7, 15), 2);
+        assert_eq!(capped.ways(7, 20), 0);
+
+        // Holding 1..=9 goes 1, 2, 4, 6, 9, 12, 15, 14, 9
+        let dragged = BoatModel {
+            drag: 2,
+            ..standard
+        };
+        assert_eq!(dragged.ways(10, 0), 9);
+        assert_eq!(dragged.ways(10, 10), 3);
+        assert_eq!(dragged.ways(10, 14), 1);
+        assert_eq!(dragged.ways(10, 15), 0);
+
+        let stalled = BoatModel {
+            acceleration: 0,
+            ..standard
+        };
+        assert_eq!(stalled.ways(10, 0), 0);
+
+        let input = vec!["Time:      7  15   30", "Distance:  9  40  200"];
+        assert_eq!(solve_with(&input, &standard), Ok((288, 71503)));
+        assert_eq!(solve_with(&input, &double).unwrap().0, 6 * 12 * 23);
+    }
+
+    #[test]
+    fn test_race_sheet() {
+        let input = ["Distance:  9  40  200", "", "Time:      7  15   30"];
+        let sheet = RaceSheet::parse(&input, Kerning::Columns).unwrap();
+        assert_eq!(
+            sheet.races,
+            vec![
+                Race {
+                    time: 7,
+                    distance: 9
+                },
+                Race {
+                    time: 15,
+                    distance: 40
+                },
+                Race {
+                    time: 30,
+                    distance: 200
+                },
+            ]
+        );
+        assert_eq!(
+            RaceSheet::parse(&input, Kerning::Merged).unwrap().races,
+            vec![Race {
+                time: 71530,
+                distance: 940200
+            }]
+        );
+    }
+
+    #[test]
+    fn test_race_sheet_errors() {
+        let err = |input: &[&str]| RaceSheet::parse(input, Kerning::Columns).unwrap_err();
+        assert_eq!(
+            err(&["Time: 7 15", "Distance: 9 40 200"]),
+            "there are 2 times but 3 distances"
+        );
+        assert_eq!(err(&["Time: 7 15"]), "there is no Distance row");
+        assert_eq!(
+            err(&["Time: 7", "Time: 8"]),
+            "line 2: there is already a Time row"
+        );
+        assert_eq!(
+            err(&["Time: 7", "Speed: 8"]),
+            "line 2: 'Speed' is not a valid row label"
+        );
+        assert_eq!(err(&["7 15"]), "line 1: '7 15' has no label");
+        assert_eq!(
+            err(&["Time: 7 x5", "Distance: 9 40"]),
+            "line 1: 'x5' is not a valid number"
+        );
+        assert_eq!(
+            RaceSheet::parse(
+                &["Time: 7", "Distance: 99999999999 99999999999"],
+                Kerning::Merged
+            )
+            .unwrap_err(),
+            "line 2: '99999999999 99999999999' is too large"
+        );
+    }
+
     #[test]
     fn test_solve_a() {
         let input = vec!["Time:      7  15   30", "Distance:  9  40  200"];
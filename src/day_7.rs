@@ -1,143 +1,117 @@
-use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Hand(String, bool); // bool is for part b Joker rule lmao
-
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
-enum Card {
-    J, // Order changes in part b, breaks part a
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    T,
-    Q,
-    K,
-    A,
+/// How Camel Cards are ranked: the order of the cards, which card (if any) is wild, and how a
+/// hand's type is worked out.
+#[derive(Clone, Debug)]
+pub struct RuleSet {
+    /// Every card, weakest first.
+    pub card_order: String,
+    /// A card that stands in for whichever card makes the strongest hand.
+    pub wildcard: Option<char>,
+    /// Works out a hand's type from how many of each card it holds, largest group first.
+    pub hand_type: fn(&[usize]) -> HandType,
 }
 
-impl From<char> for Card {
-    fn from(c: char) -> Self {
-        match c {
-            'A' => Card::A,
-            'K' => Card::K,
-            'Q' => Card::Q,
-            'J' => Card::J,
-            'T' => Card::T,
-            '9' => Card::Nine,
-            '8' => Card::Eight,
-            '7' => Card::Seven,
-            '6' => Card::Six,
-            '5' => Card::Five,
-            '4' => Card::Four,
-            '3' => Card::Three,
-            _ => Card::Two
+impl RuleSet {
+    pub fn part_a() -> Self {
+        RuleSet {
+            card_order: "23456789TJQKA".to_string(),
+            wildcard: None,
+            hand_type: standard_hand_type,
         }
     }
-}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Jokers are wild, and the weakest card when breaking ties.
+    pub fn part_b() -> Self {
+        RuleSet {
+            card_order: "J23456789TQKA".to_string(),
+            wildcard: Some('J'),
+            hand_type: standard_hand_type,
+        }
     }
-}
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let calc_hand_type = |h: &Self| {
-            let mut counts = HashMap::<char, usize>::new();
-            for c in h.0.chars() {
-                counts.entry(c)
-                    .and_modify(|count| { *count += 1})
-                    .or_insert(1);
-            }
+    fn card(&self, c: char) -> Option<Card> {
+        self.card_order
+            .chars()
+            .position(|o| o == c)
+            .map(|rank| Card(rank as u8))
+    }
 
-            let hand_type = match counts.keys().len()  {
-                1 => HandType::FiveOfAKind,
-                2 => match *counts.values().max().unwrap() {
-                    4 => HandType::FourOfAKind,
-                    _ => HandType::FullHouse,
-                },
-                3 => {
-                    let mut v = counts.values().map(|v| v.clone()).collect::<Vec<usize>>();
-                    v.sort();
-                    match &v[..] {
-                        [1, 1, 3] => HandType::ThreeOfAKind,
-                        _ => HandType::TwoPair,
-                    }
-                },
-                4 => HandType::OnePair,
-                _ => HandType::HighCard,
-            };
+    fn card_char(&self, card: Card) -> char {
+        self.card_order.chars().nth(card.0 as usize).unwrap()
+    }
 
-            let mut joker_count = *counts.get(&'J').unwrap_or(&0);
-            if !self.1 {
-                joker_count = 0;
-            }
-            return match hand_type {
-                HandType::FiveOfAKind => HandType::FiveOfAKind,
-                HandType::FourOfAKind => match joker_count {
-                    4 => HandType::FiveOfAKind,
-                    1 => HandType::FiveOfAKind,
-                    _ => HandType::FourOfAKind,
-                },
-                HandType::FullHouse => match joker_count {
-                    3 => HandType::FiveOfAKind,
-                    2 => HandType::FiveOfAKind,
-                    _ => HandType::FullHouse,
-                },
-                HandType::ThreeOfAKind => match joker_count {
-                    3 => HandType::FourOfAKind,
-                    1 => HandType::FourOfAKind,
-                    _ => HandType::ThreeOfAKind,
-                },
-                HandType::TwoPair => match joker_count {
-                    2 => HandType::FourOfAKind,
-                    1 => HandType::FullHouse,
-                    _ => HandType::TwoPair,
-                },
-                HandType::OnePair => match joker_count {
-                    2 => HandType::ThreeOfAKind,
-                    1 => HandType::ThreeOfAKind,
-                    _ => HandType::OnePair,
-                },
-                HandType::HighCard => match joker_count {
-                    1 => HandType::OnePair,
-                    _ => HandType::HighCard,
-                },
+    /// The type the cards make with every card taken at face value.
+    fn base_type(&self, cards: &[Card]) -> HandType {
+        self.classify_with(cards, None)
+    }
+
+    /// The strongest type the cards can make, trying every card the wildcards could stand in for.
+    /// Works for hands of any size.
+    fn classify(&self, cards: &[Card]) -> HandType {
+        self.classify_with(cards, self.wildcard.and_then(|w| self.card(w)))
+    }
+
+    fn classify_with(&self, cards: &[Card], wild: Option<Card>) -> HandType {
+        let mut counts = [0; MAX_CARDS];
+        let mut wildcards = 0;
+        for card in cards {
+            if Some(*card) == wild {
+                wildcards += 1;
+            } else {
+                counts[card.0 as usize] += 1;
             }
-        };
+        }
 
-        let lhs_type = calc_hand_type(self);
-        let rhs_type = calc_hand_type(other);
+        let mut counts: Vec<usize> = counts.into_iter().filter(|count| *count > 0).collect();
+        best_substitution(&mut counts, wildcards, self.hand_type)
+    }
+}
 
-        match HandType::cmp(&lhs_type, &rhs_type) {
-            std::cmp::Ordering::Equal => {
-                let lhs_cards = self.0.chars().map(|c| Card::from(c));
-                let rhs_cards = other.0.chars().map(|c| Card::from(c));
+/// Card ranks are packed into four bits each, so a rule set can't have more cards than this.
+const MAX_CARDS: usize = 16;
 
-                for (lhs, rhs) in lhs_cards.zip(rhs_cards) {
-                    let o = Card::cmp(&lhs, &rhs);
-                    if let std::cmp::Ordering::Equal = o {
-                        continue
-                    }
+/// A card's strength under the rule set it was read with, from 0 for the weakest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Card(u8);
 
-                    return o;
-                }
+/// A hand classified once, up front, so ranking hands is a plain integer sort on `key`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Hand {
+    cards: [Card; 5],
+    hand_type: HandType,
+    /// The hand type then each card's rank, four bits apiece, so stronger hands have larger keys.
+    key: u32,
+}
 
-                return std::cmp::Ordering::Equal;
-            },
-            x => x
-        }
+impl Hand {
+    fn new(s: &str, rules: &RuleSet) -> Result<Self, String> {
+        let cards: Vec<Card> = s
+            .chars()
+            .map(|c| {
+                rules
+                    .card(c)
+                    .ok_or_else(|| format!("'{}' is not a valid card", c))
+            })
+            .collect::<Result<_, _>>()?;
+        let cards: [Card; 5] = cards
+            .try_into()
+            .map_err(|_| format!("'{}' is not a hand of five cards", s))?;
+        let hand_type = rules.classify(&cards);
+        let key = cards
+            .iter()
+            .fold(hand_type as u32, |key, card| key << 4 | card.0 as u32);
+
+        Ok(Hand {
+            cards,
+            hand_type,
+            key,
+        })
     }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
-enum HandType {
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -147,46 +121,319 @@ enum HandType {
     FiveOfAKind,
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    let mut plays = Vec::<(Hand, usize)>::new();
-    for play in input {
-        let parts: Vec<&str> = play.split(" ").collect();
-        let hand = Hand(parts[0].to_string(), false);
-        let bid = parts[1].parse::<usize>().unwrap();
-        plays.push((hand, bid));
-    }
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
 
-    plays.sort_by_key(|p| p.0.clone());
-    let mut result = 0;
-    for (i, play) in plays.into_iter().enumerate() {
-        result += play.1 * (i + 1);
+        write!(f, "{}", name)
     }
+}
 
-    result
+/// The usual poker-style types. Groups of more than five still count as five of a kind.
+pub fn standard_hand_type(counts: &[usize]) -> HandType {
+    match counts {
+        [n, ..] if *n >= 5 => HandType::FiveOfAKind,
+        [4, ..] => HandType::FourOfAKind,
+        [3, 2, ..] => HandType::FullHouse,
+        [3, ..] => HandType::ThreeOfAKind,
+        [2, 2, ..] => HandType::TwoPair,
+        [2, ..] => HandType::OnePair,
+        _ => HandType::HighCard,
+    }
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    let mut plays = Vec::<(Hand, usize)>::new();
-    for play in input {
-        let parts: Vec<&str> = play.split(" ").collect();
-        let hand = Hand(parts[0].to_string(), true);
-        let bid = parts[1].parse::<usize>().unwrap();
-        plays.push((hand, bid));
+/// The best type a hand can become when each of `wildcards` either joins one of the groups of
+/// matching cards in `counts` or starts a group of its own.
+fn best_substitution(
+    counts: &mut Vec<usize>,
+    wildcards: usize,
+    hand_type: fn(&[usize]) -> HandType,
+) -> HandType {
+    if wildcards == 0 {
+        let mut sorted = counts.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        return hand_type(&sorted);
     }
 
-    plays.sort_by_key(|p| p.0.clone());
-    let mut result = 0;
-    for (i, play) in plays.into_iter().enumerate() {
-        result += play.1 * (i + 1);
+    counts.push(1);
+    let mut best = best_substitution(counts, wildcards - 1, hand_type);
+    counts.pop();
+    for idx in 0..counts.len() {
+        counts[idx] += 1;
+        best = best.max(best_substitution(counts, wildcards - 1, hand_type));
+        counts[idx] -= 1;
     }
 
-    result
+    best
+}
+
+fn parse_plays(input: &[&str], rules: &RuleSet) -> Result<Vec<(Hand, usize)>, String> {
+    if rules.card_order.chars().count() > MAX_CARDS {
+        return Err(format!(
+            "'{}' has more than {} cards",
+            rules.card_order, MAX_CARDS
+        ));
+    }
+
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, play)| {
+            let err = |e: String| format!("line {}: {}", idx + 1, e);
+            let (hand, bid) = play
+                .split_once(" ")
+                .ok_or_else(|| err(format!("'{}' is not a valid play", play)))?;
+            let hand = Hand::new(hand, rules).map_err(err)?;
+            let bid = bid
+                .parse::<usize>()
+                .map_err(|_| err(format!("'{}' is not a valid bid", bid)))?;
+
+            Ok((hand, bid))
+        })
+        .collect()
+}
+
+/// Total winnings when every hand is ranked under `rules`.
+pub fn winnings(input: &[&str], rules: &RuleSet) -> Result<usize, String> {
+    let mut plays = parse_plays(input, rules)?;
+    plays.sort_by_key(|(hand, _)| hand.key);
+
+    Ok(plays
+        .iter()
+        .enumerate()
+        .map(|(i, (_, bid))| bid * (i + 1))
+        .sum())
+}
+
+/// One line per hand, weakest first, with its cards, its type before and after wildcards, its
+/// rank, bid and winnings, and what put it above the hand ranked just below: its type, or the
+/// first card that differs.
+pub fn report(input: &[&str], rules: &RuleSet) -> Result<String, String> {
+    let mut plays = parse_plays(input, rules)?;
+    plays.sort_by_key(|(hand, _)| hand.key);
+
+    let lines: Vec<String> = plays
+        .iter()
+        .enumerate()
+        .map(|(idx, (hand, bid))| {
+            let text: String = hand.cards.iter().map(|c| rules.card_char(*c)).collect();
+            let decided = match idx.checked_sub(1).map(|below| &plays[below].0) {
+                None => "lowest hand".to_string(),
+                Some(below) if below.hand_type != hand.hand_type => {
+                    format!("beats rank {} on type", idx)
+                }
+                Some(below) => {
+                    match (0..hand.cards.len()).find(|pos| below.cards[*pos] != hand.cards[*pos]) {
+                        Some(pos) => format!(
+                            "beats rank {} on card {}, {} over {}",
+                            idx,
+                            pos + 1,
+                            rules.card_char(hand.cards[pos]),
+                            rules.card_char(below.cards[pos])
+                        ),
+                        None => format!("ties with rank {}", idx),
+                    }
+                }
+            };
+
+            format!(
+                "Rank {}: {} {} -> {}, bid {}, wins {}, {}",
+                idx + 1,
+                text,
+                rules.base_type(&hand.cards),
+                hand.hand_type,
+                bid,
+                bid * (idx + 1),
+                decided
+            )
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
+
+pub fn solve_a(input: &[&str]) -> usize {
+    winnings(input, &RuleSet::part_a()).unwrap()
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    winnings(input, &RuleSet::part_b()).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn hand(s: &str, rules: &RuleSet) -> Hand {
+        Hand::new(s, rules).unwrap()
+    }
+
+    #[test]
+    fn test_rule_sets() {
+        let (a, b) = (RuleSet::part_a(), RuleSet::part_b());
+        assert_eq!(hand("KTJJT", &a).hand_type, HandType::TwoPair);
+        assert_eq!(hand("KTJJT", &b).hand_type, HandType::FourOfAKind);
+        // J beats T in part a but is the weakest card in part b
+        assert!(hand("JKKK2", &a).key > hand("TKKK2", &a).key);
+        assert!(hand("JKKK2", &b).key < hand("QQQQ2", &b).key);
+
+        let twos_wild = RuleSet {
+            wildcard: Some('2'),
+            ..RuleSet::part_a()
+        };
+        assert_eq!(hand("2A2A3", &twos_wild).hand_type, HandType::FourOfAKind);
+        assert!(hand("22222", &twos_wild).key > hand("AAAAK", &twos_wild).key);
+
+        let aces_low = RuleSet {
+            card_order: "A23456789TJQK".to_string(),
+            ..RuleSet::part_a()
+        };
+        assert!(hand("A2345", &aces_low).key < hand("23456", &aces_low).key);
+    }
+
+    /// The joker upgrades part b used before the substitution search.
+    fn table_upgrade(hand_type: HandType, jokers: usize) -> HandType {
+        match (hand_type, jokers) {
+            (HandType::FourOfAKind, 4 | 1) => HandType::FiveOfAKind,
+            (HandType::FullHouse, 3 | 2) => HandType::FiveOfAKind,
+            (HandType::ThreeOfAKind, 3 | 1) => HandType::FourOfAKind,
+            (HandType::TwoPair, 2) => HandType::FourOfAKind,
+            (HandType::TwoPair, 1) => HandType::FullHouse,
+            (HandType::OnePair, 2 | 1) => HandType::ThreeOfAKind,
+            (HandType::HighCard, 1) => HandType::OnePair,
+            (hand_type, _) => hand_type,
+        }
+    }
+
+    #[test]
+    fn test_substitution_matches_table() {
+        let (a, b) = (RuleSet::part_a(), RuleSet::part_b());
+        let joker = b.card('J').unwrap();
+        for idx in 0..13_usize.pow(5) {
+            let cards: [Card; 5] =
+                std::array::from_fn(|i| Card((idx / 13_usize.pow(i as u32) % 13) as u8));
+            let jokers = cards.iter().filter(|c| **c == joker).count();
+            // With no wildcard the search is just the plain hand type
+            let base = a.classify(&cards);
+            assert_eq!(
+                b.classify(&cards),
+                table_upgrade(base, jokers),
+                "{:?}",
+                cards
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_hand_sizes() {
+        let rules = RuleSet::part_b();
+        let classify = |s: &str| {
+            let cards: Vec<Card> = s.chars().map(|c| rules.card(c).unwrap()).collect();
+            rules.classify(&cards)
+        };
+        assert_eq!(classify("JJK"), HandType::ThreeOfAKind);
+        assert_eq!(classify("2345"), HandType::HighCard);
+        assert_eq!(classify("J345"), HandType::OnePair);
+        assert_eq!(classify("KKQQJ2A"), HandType::FullHouse);
+        assert_eq!(classify("KKQQJJ2"), HandType::FourOfAKind);
+        assert_eq!(classify("JJJJJJ"), HandType::FiveOfAKind);
+        assert_eq!(classify(""), HandType::HighCard);
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let rules = RuleSet::part_b();
+        let h = hand("T55J5", &rules);
+        assert_eq!(h.cards, [Card(9), Card(4), Card(4), Card(0), Card(4)]);
+        // Four of a kind (5), then T, 5, 5, J, 5
+        assert_eq!(h.key, 0x0059_4404);
+
+        // Ordering by key matches ordering by type and then card by card
+        let strongest_last = [
+            "23456", "32456", "A2345", "J2345", "22345", "2233A", "J2234", "22234", "J2233",
+            "22233", "JJ223", "22223", "JJJJJ", "22222", "AAAAA",
+        ];
+        let mut hands: Vec<Hand> = strongest_last
+            .iter()
+            .rev()
+            .map(|s| hand(s, &rules))
+            .collect();
+        hands.sort_unstable_by_key(|h| h.key);
+        let expected: Vec<Hand> = strongest_last.iter().map(|s| hand(s, &rules)).collect();
+        assert_eq!(hands, expected);
+        assert!(hands
+            .windows(2)
+            .all(|pair| (pair[0].hand_type, pair[0].cards) < (pair[1].hand_type, pair[1].cards)));
+    }
+
+    #[test]
+    fn test_report() {
+        let input = [
+            "32T3K 765",
+            "T55J5 684",
+            "KK677 28",
+            "KTJJT 220",
+            "QQQJA 483",
+            "QQQJA 1",
+        ];
+        assert_eq!(
+            report(&input, &RuleSet::part_b()).unwrap(),
+            [
+                "Rank 1: 32T3K one pair -> one pair, bid 765, wins 765, lowest hand",
+                "Rank 2: KK677 two pair -> two pair, bid 28, wins 56, \
+                 beats rank 1 on type",
+                "Rank 3: T55J5 three of a kind -> four of a kind, bid 684, \
+                 wins 2052, beats rank 2 on type",
+                "Rank 4: QQQJA three of a kind -> four of a kind, bid 483, \
+                 wins 1932, beats rank 3 on card 1, Q over T",
+                "Rank 5: QQQJA three of a kind -> four of a kind, bid 1, \
+                 wins 5, ties with rank 4",
+                "Rank 6: KTJJT two pair -> four of a kind, bid 220, wins 1320, \
+                 beats rank 5 on card 1, K over Q",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_tied_hands_keep_input_order() {
+        let input = ["QQQJA 483", "QQQJA 1"];
+        let rules = RuleSet::part_b();
+        assert_eq!(winnings(&input, &rules), Ok(483 + 2));
+        assert!(report(&input, &rules).unwrap().starts_with("Rank 1: QQQJA"));
+        assert!(report(&input, &rules)
+            .unwrap()
+            .contains("bid 483, wins 483"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            winnings(&["32T3 765"], &RuleSet::part_a()),
+            Err("line 1: '32T3' is not a hand of five cards".to_string())
+        );
+        assert_eq!(
+            winnings(&["32T3K 765", "32T3X 1"], &RuleSet::part_a()),
+            Err("line 2: 'X' is not a valid card".to_string())
+        );
+        assert_eq!(
+            winnings(&["32T3K"], &RuleSet::part_a()),
+            Err("line 1: '32T3K' is not a valid play".to_string())
+        );
+        assert_eq!(
+            winnings(&["32T3K x"], &RuleSet::part_a()),
+            Err("line 1: 'x' is not a valid bid".to_string())
+        );
+    }
+
     #[test]
     fn test_solve_a() {
         let input = vec![
@@ -194,7 +441,7 @@ mod tests {
             "T55J5 684",
             "KK677 28",
             "KTJJT 220",
-            "QQQJA 483"
+            "QQQJA 483",
         ];
         assert_eq!(solve_a(&input), 6440);
     }
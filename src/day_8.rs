@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 enum Next {
     Left,
-    Right
+    Right,
 }
 
 impl From<char> for Next {
@@ -14,118 +14,144 @@ impl From<char> for Next {
     }
 }
 
-struct Node {
-    left: String,
-    right: String,
+/// The network with every node name swapped for a dense id, so a step is an array lookup.
+struct Network {
+    /// Each node's name, indexed by id.
+    names: Vec<String>,
+    /// Each node's id, keyed by name.
+    ids: HashMap<String, u32>,
+    left: Vec<u32>,
+    right: Vec<u32>,
 }
 
-pub fn solve_a(input: &Vec<&str>) -> usize {
-    let seq: Vec<Next> = input[0].chars().map(|c| Next::from(c)).collect();
-    let mut nodes = HashMap::<String, Node>::with_capacity(input.len() - 2);
-    for node_desc in input[2..].iter() {
-        let s: Vec<&str> = node_desc.split(" = ").collect(); 
-        let id = s[0].to_string();
-        let left = (&s[1][1..4]).to_string();
-        let right = (&s[1][6..9]).to_string();
-    
-        nodes.insert(id, Node {
-            left,
-            right
-        });
-    }
-
-    let mut steps = 0;
+impl Network {
+    /// Parses `AAA = (BBB, CCC)` lines. Ids are handed out in the order names first appear.
+    fn new(lines: &[&str]) -> Self {
+        let mut ids = HashMap::<String, u32>::with_capacity(lines.len());
+        let mut names = Vec::<String>::with_capacity(lines.len());
+
+        let mut edges = Vec::<(u32, u32, u32)>::with_capacity(lines.len());
+        for &node_desc in lines {
+            let (id, children) = node_desc
+                .split_once(" = ")
+                .unwrap_or_else(|| panic!("'{}' should be a node", node_desc));
+            let (left, right) = children
+                .trim_matches(|c| c == '(' || c == ')')
+                .split_once(", ")
+                .unwrap_or_else(|| panic!("'{}' should have two children", node_desc));
+
+            let mut intern = |name: &str| -> u32 {
+                if let Some(id) = ids.get(name) {
+                    return *id;
+                }
+                // u32::MAX marks an undefined node, so it can't be handed out as an id
+                let id = u32::try_from(names.len())
+                    .ok()
+                    .filter(|id| *id < u32::MAX)
+                    .expect("Network should have fewer than u32::MAX nodes");
+                ids.insert(name.to_string(), id);
+                names.push(name.to_string());
+                id
+            };
+            edges.push((intern(id), intern(left), intern(right)));
+        }
 
-    let mut node_id = "AAA".to_string();
-    let mut seq_idx = 0;
-    while node_id != "ZZZ" {
-        let next = &seq[seq_idx];
-        let node = &nodes[&node_id];
-        node_id = match next {
-            Next::Left => node.left.clone(),
-            Next::Right => node.right.clone(),
+        let mut network = Network {
+            left: vec![u32::MAX; names.len()],
+            right: vec![u32::MAX; names.len()],
+            names,
+            ids,
         };
-
-        steps += 1;
-        seq_idx += 1;
-        if seq_idx >= seq.len() {
-            seq_idx = 0;
+        for (id, left, right) in edges {
+            network.left[id as usize] = left;
+            network.right[id as usize] = right;
+        }
+        if let Some(id) = network.left.iter().position(|left| *left == u32::MAX) {
+            panic!("Node '{}' should be defined", network.names[id]);
         }
+
+        network
     }
 
-    steps
-}
+    fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
-    let seq: Vec<Next> = input[0].chars().map(|c| Next::from(c)).collect();
-    let mut nodes = HashMap::<String, Node>::with_capacity(input.len() - 2);
-    let mut visitors = Vec::<String>::new();
-    for node_desc in input[2..].iter() {
-        let s: Vec<&str> = node_desc.split(" = ").collect(); 
-        let id = s[0].to_string();
+    fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
 
-        if id.ends_with("A") {
-            visitors.push(id.clone());
+    fn step(&self, id: u32, next: &Next) -> u32 {
+        match next {
+            Next::Left => self.left[id as usize],
+            Next::Right => self.right[id as usize],
         }
-
-        let left = (&s[1][1..4]).to_string();
-        let right = (&s[1][6..9]).to_string();
-    
-        nodes.insert(id, Node {
-            left,
-            right
-        });
     }
 
-
-    let mut visitor_steps = vec![0; visitors.len()];
-    for (i, v) in visitors.iter().enumerate() {
+    /// How many steps following `seq` (repeated as needed) it takes to get from `start` to a node
+    /// that `done` accepts.
+    fn steps_until<F>(&self, start: u32, seq: &[Next], done: F) -> usize
+    where
+        F: Fn(u32) -> bool,
+    {
+        let mut id = start;
         let mut steps = 0;
-        let mut seq_idx = 0;
-        let mut node_id = v.to_string();
-        while !node_id.ends_with("Z") {
-            let next = &seq[seq_idx];
-            let node = &nodes[&node_id];
-            node_id = match next {
-                Next::Left => node.left.clone(),
-                Next::Right => node.right.clone(),
-            };
+        for next in seq.iter().cycle() {
+            if done(id) {
+                break;
+            }
 
+            id = self.step(id, next);
             steps += 1;
-            seq_idx += 1;
-            if seq_idx >= seq.len() {
-                seq_idx = 0;
-            }
         }
 
-        visitor_steps[i] = steps;
+        steps
     }
+}
 
-    fn gcd(mut a:usize, mut b:usize) -> usize{
-        if a==b { return a; }
+pub fn solve_a(input: &[&str]) -> usize {
+    let seq: Vec<Next> = input[0].chars().map(Next::from).collect();
+    let network = Network::new(&input[2..]);
+    let end = network.id("ZZZ").unwrap();
+
+    network.steps_until(network.id("AAA").unwrap(), &seq, |id| id == end)
+}
+
+pub fn solve_b(input: &[&str]) -> usize {
+    let seq: Vec<Next> = input[0].chars().map(Next::from).collect();
+    let network = Network::new(&input[2..]);
+    let is_end: Vec<bool> = network.names.iter().map(|n| n.ends_with("Z")).collect();
+
+    let visitor_steps: Vec<usize> = (0..network.names.len() as u32)
+        .filter(|id| network.name(*id).ends_with("A"))
+        .map(|id| network.steps_until(id, &seq, |id| is_end[id as usize]))
+        .collect();
+
+    fn gcd(mut a: usize, mut b: usize) -> usize {
+        if a == b {
+            return a;
+        }
         if b > a {
-            let temp = a;
-            a = b;
-            b = temp;
+            std::mem::swap(&mut a, &mut b);
         }
-        while b>0 {
+        while b > 0 {
             let temp = a;
             a = b;
-            b = temp%b;
+            b = temp % b;
         }
-        return a;
+        a
     }
 
-    fn lcm(a:usize, b:usize) -> usize{
+    fn lcm(a: usize, b: usize) -> usize {
         // LCM = a*b / gcd
-        return a*(b/gcd(a,b));
+        a * (b / gcd(a, b))
     }
 
     let mut x = lcm(visitor_steps[0], 1);
     for s in visitor_steps[1..].iter() {
         x = lcm(x, *s);
     }
-    
+
     x
 }
 
@@ -133,6 +159,23 @@ pub fn solve_b(input: &Vec<&str>) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_network() {
+        let network = Network::new(&["AAA = (BBB, CCC)", "CCC = (AAA, BBB)", "BBB = (BBB, BBB)"]);
+        assert_eq!(network.names, vec!["AAA", "BBB", "CCC"]);
+        assert_eq!(network.left, vec![1, 1, 0]);
+        assert_eq!(network.right, vec![2, 1, 1]);
+        assert_eq!(network.id("CCC"), Some(2));
+        assert_eq!(network.id("ZZZ"), None);
+        assert_eq!(network.name(network.step(2, &Next::Left)), "AAA");
+    }
+
+    #[test]
+    #[should_panic(expected = "Node 'CCC' should be defined")]
+    fn test_network_missing_node() {
+        Network::new(&["AAA = (BBB, CCC)", "BBB = (BBB, BBB)"]);
+    }
+
     #[test]
     fn test_solve_a() {
         let input = vec![
@@ -148,7 +191,6 @@ mod tests {
         ];
         assert_eq!(solve_a(&input), 2);
 
-        
         let input = vec![
             "LLR",
             "",
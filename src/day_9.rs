@@ -1,4 +1,4 @@
-pub fn solve_a(input: &Vec<&str>) -> usize {
+pub fn solve_a(input: &[&str]) -> usize {
     let mut sum = 0;
 
     fn seq_next(digits: &[isize]) -> isize {
@@ -20,7 +20,7 @@ pub fn solve_a(input: &Vec<&str>) -> usize {
     sum as usize
 }
 
-pub fn solve_b(input: &Vec<&str>) -> usize {
+pub fn solve_b(input: &[&str]) -> usize {
     let mut sum = 0;
 
     fn seq_prev(digits: &[isize]) -> isize {
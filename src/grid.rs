@@ -0,0 +1,395 @@
+// A general grid type shared by the grid puzzles, so not every helper has a caller yet.
+#![allow(dead_code)]
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, column: usize) -> Self {
+        Coord { row, column }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    /// A line's length (in characters) didn't match the first line's.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} columns, expected {}",
+                row, found, expected
+            ),
+        }
+    }
+}
+
+/// A rectangular grid stored row-major in a single `Vec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    columns: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Vec<T>, rows: usize, columns: usize) -> Self {
+        assert_eq!(
+            cells.len(),
+            rows * columns,
+            "Grid should have rows * columns cells"
+        );
+
+        Grid {
+            cells,
+            rows,
+            columns,
+        }
+    }
+
+    /// Builds a grid with one cell per character, failing if the lines aren't all the same length.
+    pub fn from_lines<F>(lines: &[&str], mut parse: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> T,
+    {
+        let rows = lines.len();
+        let columns = lines.first().map_or(0, |line| line.chars().count());
+        let mut cells = Vec::with_capacity(rows * columns);
+        for (row, line) in lines.iter().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut parse));
+            let found = cells.len() - before;
+            if found != columns {
+                return Err(GridError::Ragged {
+                    row,
+                    expected: columns,
+                    found,
+                });
+            }
+        }
+
+        Ok(Grid {
+            cells,
+            rows,
+            columns,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.row < self.rows && coord.column < self.columns
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        if self.contains(coord) {
+            Some(&self.cells[self.index_of(coord)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        if self.contains(coord) {
+            let idx = self.index_of(coord);
+            Some(&mut self.cells[idx])
+        } else {
+            None
+        }
+    }
+
+    fn index_of(&self, coord: Coord) -> usize {
+        coord.row * self.columns + coord.column
+    }
+
+    fn coord_of(&self, idx: usize) -> Coord {
+        Coord::new(idx / self.columns, idx % self.columns)
+    }
+
+    /// Every cell in row-major order along with its coordinate.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (self.coord_of(idx), cell))
+    }
+
+    /// The coordinate `d_row` rows and `d_column` columns away, if it's inside the grid.
+    pub fn offset(&self, coord: Coord, d_row: isize, d_column: isize) -> Option<Coord> {
+        let row = coord.row.checked_add_signed(d_row)?;
+        let column = coord.column.checked_add_signed(d_column)?;
+        let coord = Coord::new(row, column);
+        if self.contains(coord) {
+            Some(coord)
+        } else {
+            None
+        }
+    }
+
+    /// North, west, east and south neighbours that are inside the grid.
+    pub fn neighbours4(&self, coord: Coord) -> Vec<Coord> {
+        [(-1, 0), (0, -1), (0, 1), (1, 0)]
+            .into_iter()
+            .filter_map(|(d_row, d_column)| self.offset(coord, d_row, d_column))
+            .collect()
+    }
+
+    /// All eight surrounding neighbours that are inside the grid, in row-major order.
+    pub fn neighbours8(&self, coord: Coord) -> Vec<Coord> {
+        let mut results = Vec::<Coord>::new();
+        for d_row in -1..=1 {
+            for d_column in -1..=1 {
+                if d_row == 0 && d_column == 0 {
+                    continue;
+                }
+
+                if let Some(neighbour) = self.offset(coord, d_row, d_column) {
+                    results.push(neighbour);
+                }
+            }
+        }
+
+        results
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        (0..self.rows).map(move |row| &self[Coord::new(row, column)])
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.rows).map(|row| self.row(row))
+    }
+
+    pub fn iter_columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.columns).map(|column| self.column(column))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            rows: self.rows,
+            columns: self.columns,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Self {
+        self.rebuild(self.columns, self.rows, |row, column| {
+            Coord::new(column, row)
+        })
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        self.rebuild(self.columns, self.rows, |row, column| {
+            Coord::new(self.rows - 1 - column, row)
+        })
+    }
+
+    pub fn rotate_anticlockwise(&self) -> Self {
+        self.rebuild(self.columns, self.rows, |row, column| {
+            Coord::new(column, self.columns - 1 - row)
+        })
+    }
+
+    /// Builds a `rows` x `columns` grid where each cell is copied from `source(row, column)`.
+    fn rebuild<F>(&self, rows: usize, columns: usize, source: F) -> Self
+    where
+        F: Fn(usize, usize) -> Coord,
+    {
+        let mut cells = Vec::with_capacity(rows * columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                cells.push(self[source(row, column)].clone());
+            }
+        }
+
+        Grid {
+            cells,
+            rows,
+            columns,
+        }
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &Self::Output {
+        assert!(
+            self.contains(coord),
+            "{:?} should be inside the grid",
+            coord
+        );
+        &self.cells[self.index_of(coord)]
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut Self::Output {
+        assert!(
+            self.contains(coord),
+            "{:?} should be inside the grid",
+            coord
+        );
+        let idx = self.index_of(coord);
+        &mut self.cells[idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(rows: usize, columns: usize) -> Grid<usize> {
+        Grid::new((0..rows * columns).collect(), rows, columns)
+    }
+
+    fn coords(coords: &[(usize, usize)]) -> Vec<Coord> {
+        coords.iter().map(|(r, c)| Coord::new(*r, *c)).collect()
+    }
+
+    #[test]
+    fn test_from_lines() {
+        let grid = Grid::from_lines(&["ab", "cd", "ef"], |c| c).unwrap();
+        assert_eq!(grid, Grid::new(vec!['a', 'b', 'c', 'd', 'e', 'f'], 3, 2));
+
+        let grid = Grid::from_lines(&["é#", "✱."], |c| c).unwrap();
+        assert_eq!(grid.columns(), 2);
+        assert_eq!(grid[Coord::new(1, 0)], '✱');
+
+        assert_eq!(
+            Grid::from_lines(&["abc", "de", "fgh"], |c| c),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Grid::from_lines(&[], |c| c),
+            Ok(Grid::new(Vec::<char>::new(), 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_neighbours8() {
+        let grid = numbered(5, 5);
+        let neighbours = |row, column| -> Vec<usize> {
+            grid.neighbours8(Coord::new(row, column))
+                .into_iter()
+                .map(|c| grid[c])
+                .collect()
+        };
+        // North + West boundary checks
+        assert_eq!(neighbours(0, 0), vec![1, 5, 6]);
+        // North boundary check
+        assert_eq!(neighbours(0, 1), vec![0, 2, 5, 6, 7]);
+        // North + East boundary check
+        assert_eq!(neighbours(0, 4), vec![3, 8, 9]);
+        // No boundaries
+        assert_eq!(neighbours(1, 3), vec![2, 3, 4, 7, 9, 12, 13, 14]);
+        // South + West boundary checks
+        assert_eq!(neighbours(4, 0), vec![15, 16, 21]);
+        // South boundary checks
+        assert_eq!(neighbours(4, 1), vec![15, 16, 17, 20, 22]);
+        // South + East boundary checks
+        assert_eq!(neighbours(4, 4), vec![18, 19, 23]);
+        // West boundary checks
+        assert_eq!(neighbours(1, 0), vec![0, 1, 6, 10, 11]);
+        // East boundary checks
+        assert_eq!(neighbours(1, 4), vec![3, 4, 8, 13, 14]);
+    }
+
+    #[test]
+    fn test_neighbours4() {
+        let grid = numbered(3, 3);
+        assert_eq!(
+            grid.neighbours4(Coord::new(0, 0)),
+            coords(&[(0, 1), (1, 0)])
+        );
+        assert_eq!(
+            grid.neighbours4(Coord::new(1, 1)),
+            coords(&[(0, 1), (1, 0), (1, 2), (2, 1)])
+        );
+        assert_eq!(
+            grid.neighbours4(Coord::new(2, 2)),
+            coords(&[(1, 2), (2, 1)])
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = numbered(2, 3);
+        assert_eq!(
+            grid.iter_rows().collect::<Vec<&[usize]>>(),
+            vec![&[0, 1, 2][..], &[3, 4, 5][..]]
+        );
+        assert_eq!(
+            grid.iter_columns()
+                .map(|c| c.copied().collect())
+                .collect::<Vec<Vec<usize>>>(),
+            vec![vec![0, 3], vec![1, 4], vec![2, 5]]
+        );
+        assert_eq!(grid.get(Coord::new(1, 2)), Some(&5));
+        assert_eq!(grid.get(Coord::new(2, 0)), None);
+        assert_eq!(grid.offset(Coord::new(0, 0), -1, 0), None);
+        assert_eq!(grid.offset(Coord::new(0, 0), 1, 2), Some(Coord::new(1, 2)));
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        // 0 1 2
+        // 3 4 5
+        let grid = numbered(2, 3);
+        assert_eq!(grid.transpose(), Grid::new(vec![0, 3, 1, 4, 2, 5], 3, 2));
+        assert_eq!(
+            grid.rotate_clockwise(),
+            Grid::new(vec![3, 0, 4, 1, 5, 2], 3, 2)
+        );
+        assert_eq!(
+            grid.rotate_anticlockwise(),
+            Grid::new(vec![2, 5, 1, 4, 0, 3], 3, 2)
+        );
+        assert_eq!(grid.rotate_clockwise().rotate_anticlockwise(), grid);
+        assert_eq!(
+            grid.rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise(),
+            grid
+        );
+    }
+}
@@ -0,0 +1,491 @@
+// General range types shared by the range puzzles, so not every helper has a caller yet.
+#![allow(dead_code)]
+
+use std::cmp;
+use std::fmt;
+use std::ops::Range;
+
+/// A set of values stored as sorted, disjoint, half-open ranges. Ranges that touch are merged,
+/// so two sets holding the same values always compare equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<usize>>,
+}
+
+impl IntervalSet {
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map(|r| r.len()).sum()
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.ranges
+            .binary_search_by(|r| {
+                if r.end <= value {
+                    cmp::Ordering::Less
+                } else if r.start > value {
+                    cmp::Ordering::Greater
+                } else {
+                    cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub fn min(&self) -> Option<usize> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.ranges
+            .iter()
+            .chain(other.ranges.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::<Range<usize>>::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = cmp::max(a.start, b.start);
+            let end = cmp::min(a.end, b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        ranges.into_iter().collect()
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::<Range<usize>>::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if other.ranges[k].start > start {
+                    ranges.push(start..other.ranges[k].start);
+                }
+                start = cmp::max(start, other.ranges[k].end);
+                k += 1;
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        ranges.into_iter().collect()
+    }
+
+    /// Shifts every value by `by`. Panics if a value would leave the range of `usize`.
+    pub fn offset(&self, by: isize) -> IntervalSet {
+        let shift = |v: usize| {
+            v.checked_add_signed(by)
+                .expect("Offset values should stay within usize")
+        };
+
+        IntervalSet {
+            ranges: self
+                .ranges
+                .iter()
+                .map(|r| shift(r.start)..shift(r.end))
+                .collect(),
+        }
+    }
+}
+
+impl FromIterator<Range<usize>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<usize>>>(iter: I) -> Self {
+        let mut ranges: Vec<Range<usize>> = iter.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_by_key(|r| r.start);
+
+        let mut merged = Vec::<Range<usize>>::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = cmp::max(last.end, range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        IntervalSet { ranges: merged }
+    }
+}
+
+/// Maps values by adding the offset of the piece they fall in. Values outside every piece map
+/// to themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    /// Sorted by start and never overlapping.
+    pieces: Vec<(Range<usize>, isize)>,
+}
+
+impl PiecewiseMap {
+    /// Panics if any two pieces overlap.
+    pub fn new<I: IntoIterator<Item = (Range<usize>, isize)>>(pieces: I) -> Self {
+        Self::try_new(pieces).unwrap_or_else(|(first, second)| {
+            panic!(
+                "PiecewiseMap pieces {:?} and {:?} should not overlap",
+                first, second
+            )
+        })
+    }
+
+    /// Like `new`, but hands back the first two overlapping pieces (by start) instead of
+    /// panicking.
+    pub fn try_new<I: IntoIterator<Item = (Range<usize>, isize)>>(
+        pieces: I,
+    ) -> Result<Self, (Range<usize>, Range<usize>)> {
+        let mut pieces: Vec<(Range<usize>, isize)> =
+            pieces.into_iter().filter(|(r, _)| !r.is_empty()).collect();
+        pieces.sort_by_key(|(r, _)| r.start);
+        if let Some(pair) = pieces
+            .windows(2)
+            .find(|pair| pair[0].0.end > pair[1].0.start)
+        {
+            return Err((pair[0].0.clone(), pair[1].0.clone()));
+        }
+
+        Ok(PiecewiseMap { pieces })
+    }
+
+    pub fn pieces(&self) -> &[(Range<usize>, isize)] {
+        &self.pieces
+    }
+
+    /// Every value covered by a piece.
+    pub fn domain(&self) -> IntervalSet {
+        self.pieces.iter().map(|(r, _)| r.clone()).collect()
+    }
+
+    pub fn apply(&self, value: usize) -> usize {
+        let idx = self.pieces.partition_point(|(r, _)| r.start <= value);
+        match idx.checked_sub(1).map(|idx| &self.pieces[idx]) {
+            Some((range, offset)) if range.contains(&value) => value
+                .checked_add_signed(*offset)
+                .expect("Mapped values should stay within usize"),
+            _ => value,
+        }
+    }
+
+    /// The image of every value in `set`.
+    pub fn apply_set(&self, set: &IntervalSet) -> IntervalSet {
+        let mut result = set.difference(&self.domain());
+        for (range, offset) in &self.pieces {
+            let piece: IntervalSet = [range.clone()].into_iter().collect();
+            result = result.union(&set.intersection(&piece).offset(*offset));
+        }
+
+        result
+    }
+
+    /// Every value that `apply` sends into `set`. Several values can share an image, so this may
+    /// be larger than `set`.
+    pub fn preimage(&self, set: &IntervalSet) -> IntervalSet {
+        let mut result = set.difference(&self.domain());
+        for (range, offset) in &self.pieces {
+            let image: IntervalSet = std::iter::once(range.clone())
+                .collect::<IntervalSet>()
+                .offset(*offset);
+            result = result.union(&set.intersection(&image).offset(-offset));
+        }
+
+        result
+    }
+
+    /// A single map that behaves like applying `self` and then `then`.
+    pub fn compose(&self, then: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = Vec::<(Range<usize>, isize)>::new();
+        let then_domain = then.domain();
+        for (range, offset) in &self.pieces {
+            let image: IntervalSet = std::iter::once(range.clone())
+                .collect::<IntervalSet>()
+                .offset(*offset);
+            for (then_range, then_offset) in &then.pieces {
+                let then_piece: IntervalSet = std::iter::once(then_range.clone()).collect();
+                for part in image.intersection(&then_piece).offset(-offset).ranges() {
+                    pieces.push((part.clone(), offset + then_offset));
+                }
+            }
+            for part in image.difference(&then_domain).offset(-offset).ranges() {
+                pieces.push((part.clone(), *offset));
+            }
+        }
+
+        // Values `self` leaves alone go straight into `then`
+        let self_domain = self.domain();
+        for (then_range, then_offset) in &then.pieces {
+            let then_piece: IntervalSet = std::iter::once(then_range.clone()).collect();
+            for part in then_piece.difference(&self_domain).ranges() {
+                pieces.push((part.clone(), *then_offset));
+            }
+        }
+
+        pieces.retain(|(_, offset)| *offset != 0);
+        pieces.sort_by_key(|(r, _)| r.start);
+        let mut merged = Vec::<(Range<usize>, isize)>::with_capacity(pieces.len());
+        for (range, offset) in pieces {
+            match merged.last_mut() {
+                Some((last, last_offset)) if last.end == range.start && *last_offset == offset => {
+                    last.end = range.end
+                }
+                _ => merged.push((range, offset)),
+            }
+        }
+
+        PiecewiseMap { pieces: merged }
+    }
+}
+
+impl fmt::Display for PiecewiseMap {
+    /// One line per piece, e.g. `50..98 -> 52..100`. Values not listed map to themselves.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (range, offset)) in self.pieces.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}..{} -> {}..{}",
+                range.start,
+                range.end,
+                range.start as isize + offset,
+                range.end as isize + offset
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<usize>]) -> IntervalSet {
+        ranges.iter().cloned().collect()
+    }
+
+    /// Small xorshift generator so the fuzz tests are repeatable without extra dependencies.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, below: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % below as u64) as usize
+        }
+
+        fn set(&mut self, limit: usize) -> IntervalSet {
+            (0..self.next(5))
+                .map(|_| {
+                    let start = self.next(limit);
+                    start..start + self.next(limit / 4)
+                })
+                .collect()
+        }
+
+        fn map(&mut self, limit: usize) -> PiecewiseMap {
+            let mut pieces = vec![];
+            let mut start = self.next(limit / 4);
+            for _ in 0..self.next(5) {
+                let end = start + self.next(limit / 4);
+                let offset = self.next(limit) as isize - start as isize;
+                pieces.push((start..end, offset));
+                start = end + self.next(limit / 8);
+            }
+
+            PiecewiseMap::new(pieces)
+        }
+    }
+
+    fn values(set: &IntervalSet) -> Vec<usize> {
+        set.ranges().iter().flat_map(|r| r.clone()).collect()
+    }
+
+    #[test]
+    fn test_from_ranges_normalises() {
+        assert_eq!(
+            set(&[5..8, 0..2, 2..3, 7..10, 12..12]).ranges(),
+            &[0..3, 5..10]
+        );
+        assert_eq!(set(&[]), IntervalSet::default());
+    }
+
+    #[test]
+    fn test_set_queries() {
+        let s = set(&[3..5, 10..20]);
+        assert_eq!(s.len(), 12);
+        assert_eq!(s.min(), Some(3));
+        assert_eq!(s.max(), Some(19));
+        assert!(s.contains(3));
+        assert!(!s.contains(5));
+        assert!(s.contains(19));
+        assert!(!s.contains(20));
+        assert_eq!(IntervalSet::default().min(), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25]);
+        assert_eq!(a.union(&b).ranges(), &[0..30]);
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..30]);
+        assert_eq!(b.difference(&a).ranges(), &[10..20]);
+        assert_eq!(a.offset(-0).ranges(), a.ranges());
+        assert_eq!(set(&[5..10]).offset(-5).ranges(), &[0..5]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let map = PiecewiseMap::new([(98..100, -48), (50..98, 2)]);
+        assert_eq!(map.apply(0), 0);
+        assert_eq!(map.apply(49), 49);
+        assert_eq!(map.apply(50), 52);
+        assert_eq!(map.apply(97), 99);
+        assert_eq!(map.apply(98), 50);
+        assert_eq!(map.apply(99), 51);
+        assert_eq!(map.apply(100), 100);
+    }
+
+    #[test]
+    fn test_apply_set_fills_gaps_exactly() {
+        // The value straight after a piece maps to itself
+        let map = PiecewiseMap::new([(2..4, 100)]);
+        assert_eq!(
+            map.apply_set(&set(&[0..10])).ranges(),
+            &[0..2, 4..10, 102..104]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let first = PiecewiseMap::new([(0..10, 10)]);
+        let second = PiecewiseMap::new([(5..15, -5), (20..30, 1)]);
+        let composed = first.compose(&second);
+        assert_eq!(
+            composed.pieces(),
+            &[(0..5, 5), (5..10, 10), (10..15, -5), (20..30, 1)]
+        );
+        assert_eq!(
+            composed.to_string(),
+            "0..5 -> 5..10\n5..10 -> 15..20\n10..15 -> 5..10\n20..30 -> 21..31"
+        );
+
+        // Pieces that cancel out are dropped
+        let back = PiecewiseMap::new([(10..20, -10)]);
+        assert_eq!(first.compose(&back), back);
+        assert_eq!(PiecewiseMap::new([]).to_string(), "");
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            PiecewiseMap::try_new([(4..6, 2), (0..5, 1)]),
+            Err((0..5, 4..6))
+        );
+        assert_eq!(
+            PiecewiseMap::try_new([(5..6, 2), (0..5, 1)]),
+            Ok(PiecewiseMap::new([(0..5, 1), (5..6, 2)]))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overlapping_pieces() {
+        PiecewiseMap::new([(0..5, 1), (4..6, 2)]);
+    }
+
+    #[test]
+    fn test_fuzz_set_operations() {
+        let mut rng = Rng(0x5eed);
+        for _ in 0..500 {
+            let (a, b) = (rng.set(64), rng.set(64));
+            let (a_values, b_values) = (values(&a), values(&b));
+            let brute = |keep: &dyn Fn(bool, bool) -> bool| -> Vec<usize> {
+                (0..128)
+                    .filter(|v| keep(a_values.contains(v), b_values.contains(v)))
+                    .collect()
+            };
+
+            assert_eq!(values(&a.union(&b)), brute(&|x, y| x || y));
+            assert_eq!(values(&a.intersection(&b)), brute(&|x, y| x && y));
+            assert_eq!(values(&a.difference(&b)), brute(&|x, y| x && !y));
+            assert_eq!(a.len(), a_values.len());
+        }
+    }
+
+    #[test]
+    fn test_fuzz_apply_set() {
+        let mut rng = Rng(0xa11ce);
+        for _ in 0..500 {
+            let map = rng.map(64);
+            let input = rng.set(64);
+            let mut expected: Vec<usize> = values(&input).iter().map(|v| map.apply(*v)).collect();
+            expected.sort();
+            expected.dedup();
+
+            assert_eq!(values(&map.apply_set(&input)), expected);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_preimage() {
+        let mut rng = Rng(0xbacc);
+        for _ in 0..500 {
+            let map = rng.map(64);
+            let target = rng.set(128);
+            let expected: Vec<usize> = (0..192)
+                .filter(|v| target.contains(map.apply(*v)))
+                .collect();
+
+            assert_eq!(values(&map.preimage(&target)), expected);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_compose() {
+        let mut rng = Rng(0xc0de);
+        for _ in 0..500 {
+            let (first, second) = (rng.map(64), rng.map(64));
+            let composed = first.compose(&second);
+            for value in 0..192 {
+                assert_eq!(composed.apply(value), second.apply(first.apply(value)));
+            }
+
+            let input = rng.set(64);
+            assert_eq!(
+                composed.apply_set(&input),
+                second.apply_set(&first.apply_set(&input))
+            );
+        }
+    }
+}
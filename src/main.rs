@@ -7,17 +7,327 @@ mod day_6;
 mod day_7;
 mod day_8;
 mod day_9;
+mod grid;
+mod interval;
 
+use std::env;
+use std::fmt;
 use std::fs;
+use std::process;
+
+const USAGE: &str = "Usage: aoc2023 [COMMAND]
+
+Commands:
+    day_2 json [INPUT]                  Export parsed game records as JSON
+    day_2 analyse [INPUT]               Estimate the most likely 39 cube bag for each game
+    day_2 likelihood R G B [INPUT]      Likelihood of each game given a proposed bag
+    day_2 minimal-bag GAMES [INPUT]     Smallest bag for GAMES (comma separated ids, or 'all')
+    day_2 ruled-out R G B [INPUT]       Games that couldn't have been played with a bag
+    day_2 power COLOURS [INPUT]         Power sum over COLOURS (comma separated, e.g. red,blue)
+    day_3 rule RULE [INPUT]             Sum a symbol rule, e.g. 'gears' or '*# >=2 sum'
+    day_3 render ansi|html [INPUT]      Show which numbers are parts and which symbols are gears
+    day_4 report [INPUT]                Points and copies won for each scratchcard
+    day_4 variant SCORING COPIES [INPUT]
+                                        Play with other rules: SCORING is doubling or linear,
+                                        COPIES is next, capped:N, previous or wrapping
+    day_5 compose [INPUT]               Print the almanac as one seed to location map
+    day_5 convert FROM TO [INPUT]       Print the chain of maps from FROM to TO as one map
+    day_5 preimage FROM TO START..END [INPUT]
+                                        FROM ranges that convert into TO values START..END
+    day_5 locations SEEDS [INPUT]       Where SEEDS end up: values, pairs or e.g. 79..93,55..68
+    day_6 boat ACCEL TOP DRAG [INPUT]   Both parts for a different boat (TOP may be 'none')
+    day_7 rules ORDER WILD [INPUT]      Winnings with cards ranked ORDER (weakest first, e.g.
+                                        23456789TJQKA) and WILD as the wildcard, or 'none'
+    day_7 report a|b [INPUT]            Every hand's type, rank and winnings, and its tie-break";
 
 struct Day {
     input_a: String,
     input_b: String,
-    solve_a: fn(&Vec<&str>) -> usize,
-    solve_b: fn(&Vec<&str>) -> usize,
+    solve_a: fn(&[&str]) -> usize,
+    solve_b: fn(&[&str]) -> usize,
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args[..] {
+        [] => solve_all(),
+        ["day_2", "json"] => day_2_json("day_2a.in"),
+        ["day_2", "json", path] => day_2_json(path),
+        ["day_2", "analyse"] => day_2_analyse("day_2a.in"),
+        ["day_2", "analyse", path] => day_2_analyse(path),
+        ["day_2", "likelihood", r, g, b] => day_2_likelihood(parse_bag(r, g, b), "day_2a.in"),
+        ["day_2", "likelihood", r, g, b, path] => day_2_likelihood(parse_bag(r, g, b), path),
+        ["day_2", "minimal-bag", games] => day_2_minimal_bag(games, "day_2a.in"),
+        ["day_2", "minimal-bag", games, path] => day_2_minimal_bag(games, path),
+        ["day_2", "ruled-out", r, g, b] => day_2_ruled_out(parse_bag(r, g, b), "day_2a.in"),
+        ["day_2", "ruled-out", r, g, b, path] => day_2_ruled_out(parse_bag(r, g, b), path),
+        ["day_2", "power", colours] => day_2_power(colours, "day_2a.in"),
+        ["day_2", "power", colours, path] => day_2_power(colours, path),
+        ["day_3", "rule", rule] => day_3_rule(rule, "day_3a.in"),
+        ["day_3", "rule", rule, path] => day_3_rule(rule, path),
+        ["day_3", "render", format] => day_3_render(format, "day_3a.in"),
+        ["day_3", "render", format, path] => day_3_render(format, path),
+        ["day_4", "report"] => day_4_report("day_4a.in"),
+        ["day_4", "report", path] => day_4_report(path),
+        ["day_4", "variant", scoring, copies] => day_4_variant(scoring, copies, "day_4a.in"),
+        ["day_4", "variant", scoring, copies, path] => day_4_variant(scoring, copies, path),
+        ["day_5", "compose"] => day_5_convert("seed", "location", "day_5a.in"),
+        ["day_5", "compose", path] => day_5_convert("seed", "location", path),
+        ["day_5", "convert", from, to] => day_5_convert(from, to, "day_5a.in"),
+        ["day_5", "convert", from, to, path] => day_5_convert(from, to, path),
+        ["day_5", "preimage", from, to, range] => day_5_preimage(from, to, range, "day_5a.in"),
+        ["day_5", "preimage", from, to, range, path] => day_5_preimage(from, to, range, path),
+        ["day_5", "locations", seeds] => day_5_locations(seeds, "day_5a.in"),
+        ["day_5", "locations", seeds, path] => day_5_locations(seeds, path),
+        ["day_6", "boat", accel, top, drag] => day_6_boat(accel, top, drag, "day_6a.in"),
+        ["day_6", "boat", accel, top, drag, path] => day_6_boat(accel, top, drag, path),
+        ["day_7", "rules", order, wild] => day_7_rules(order, wild, "day_7a.in"),
+        ["day_7", "rules", order, wild, path] => day_7_rules(order, wild, path),
+        ["day_7", "report", part] => day_7_report(part, "day_7a.in"),
+        ["day_7", "report", part, path] => day_7_report(part, path),
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn parse_bag(red: &str, green: &str, blue: &str) -> [usize; 3] {
+    match (red.parse(), green.parse(), blue.parse()) {
+        (Ok(red), Ok(green), Ok(blue)) => [red, green, blue],
+        _ => usage(),
+    }
+}
+
+fn read_input(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| input_error(path, e))
+}
+
+fn day_2_json(path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::to_json(&input) {
+        Ok(json) => println!("{}", json),
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn day_2_analyse(path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::most_likely_bags(&input, day_2::BAG_SIZE) {
+        Ok(estimates) => print_bag_estimates(&estimates),
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn day_2_likelihood(bag: [usize; 3], path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::bag_likelihoods(&input, bag) {
+        Ok(estimates) => print_bag_estimates(&estimates),
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn day_2_minimal_bag(games: &str, path: &str) {
+    let games: Option<Vec<usize>> = match games {
+        "all" => None,
+        games => Some(
+            games
+                .split(',')
+                .map(|id| id.parse().unwrap_or_else(|_| usage()))
+                .collect(),
+        ),
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::minimal_bag(&input, games.as_deref()) {
+        Ok(bag) => println!("{} red, {} green, {} blue", bag[0], bag[1], bag[2]),
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn day_2_ruled_out(bag: [usize; 3], path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::ruled_out(&input, bag) {
+        Ok(games) => {
+            for game in games {
+                println!("Game {}", game);
+            }
+        }
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn day_2_power(colours: &str, path: &str) {
+    let colours: Vec<day_2::CubeColour> = colours
+        .split(',')
+        .map(|colour| colour.parse().unwrap_or_else(|_| usage()))
+        .collect();
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_2::power_sum(&input, &colours) {
+        Ok(sum) => println!("{}", sum),
+        Err(e) => day_2_parse_error(path, &input, e),
+    }
+}
+
+fn print_bag_estimates(estimates: &[day_2::BagEstimate]) {
+    for estimate in estimates {
+        println!(
+            "Game {}: {} red, {} green, {} blue (likelihood {:.6e})",
+            estimate.game, estimate.bag[0], estimate.bag[1], estimate.bag[2], estimate.likelihood
+        );
+    }
+}
+
+fn input_error(path: &str, e: impl fmt::Display) -> ! {
+    eprintln!("{}: {}", path, e);
+    process::exit(1);
+}
+
+fn day_2_parse_error(path: &str, input: &[&str], (idx, e): (usize, day_2::ParseError)) -> ! {
+    eprintln!("{}:{}: {}", path, idx + 1, e);
+    eprintln!("{}", e.underline(input[idx]));
+    process::exit(1);
+}
+
+fn day_3_rule(rule: &str, path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_3::apply_rule(&input, rule) {
+        Ok(sum) => println!("{}", sum),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_3_render(format: &str, path: &str) {
+    let format = match format {
+        "ansi" => day_3::RenderFormat::Ansi,
+        "html" => day_3::RenderFormat::Html,
+        _ => usage(),
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_3::render_schematic(&input, format) {
+        Ok(rendered) => println!("{}", rendered),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_4_report(path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_4::report(&input) {
+        Ok(report) => println!("{}", report),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_4_variant(scoring: &str, copies: &str, path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_4::play_variant(&input, scoring, copies) {
+        Ok((points, cards)) => println!("{} points, {} cards", points, cards),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_5_convert(from: &str, to: &str, path: &str) {
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_5::conversion(&input, from, to) {
+        Ok(conversion) => println!("{}", conversion),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_5_preimage(from: &str, to: &str, range: &str, path: &str) {
+    let targets = match range.split_once("..").map(|(s, e)| (s.parse(), e.parse())) {
+        Some((Ok(start), Ok(end))) => std::iter::once(start..end).collect(),
+        _ => usage(),
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_5::preimage(&input, from, to, targets) {
+        Ok(ranges) => println!("{}", ranges),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_5_locations(seeds: &str, path: &str) {
+    let spec: day_5::SeedSpec = seeds.parse().unwrap_or_else(|_| usage());
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_5::location_report(&input, &spec) {
+        Ok(report) => println!("{}", report),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_6_boat(acceleration: &str, top_speed: &str, drag: &str, path: &str) {
+    let top_speed = match top_speed {
+        "none" => None,
+        top_speed => Some(top_speed.parse().unwrap_or_else(|_| usage())),
+    };
+    let model = match (acceleration.parse(), drag.parse()) {
+        (Ok(acceleration), Ok(drag)) => day_6::BoatModel {
+            acceleration,
+            top_speed,
+            drag,
+        },
+        _ => usage(),
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_6::solve_with(&input, &model) {
+        Ok((a, b)) => println!("{} | {}", a, b),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_7_rules(order: &str, wildcard: &str, path: &str) {
+    let wildcard = match wildcard {
+        "none" => None,
+        wildcard if wildcard.chars().count() == 1 && order.contains(wildcard) => {
+            wildcard.chars().next()
+        }
+        _ => usage(),
+    };
+    let rules = day_7::RuleSet {
+        card_order: order.to_string(),
+        wildcard,
+        ..day_7::RuleSet::part_a()
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_7::winnings(&input, &rules) {
+        Ok(winnings) => println!("{}", winnings),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn day_7_report(part: &str, path: &str) {
+    let rules = match part {
+        "a" => day_7::RuleSet::part_a(),
+        "b" => day_7::RuleSet::part_b(),
+        _ => usage(),
+    };
+    let contents = read_input(path);
+    let input: Vec<&str> = contents.lines().collect();
+    match day_7::report(&input, &rules) {
+        Ok(report) => println!("{}", report),
+        Err(e) => input_error(path, e),
+    }
+}
+
+fn solve_all() {
     let days = vec![
         Day {
             input_a: "day_1a.in".to_string(),
@@ -77,11 +387,11 @@ fn main() {
 
     for (idx, day) in days.iter().enumerate() {
         let a_contents = fs::read_to_string(&day.input_a)
-            .expect(&format!("Day {}a input file should be present", idx + 1));
-        let input_a = a_contents.lines().collect();
+            .unwrap_or_else(|_| panic!("Day {}a input file should be present", idx + 1));
+        let input_a: Vec<&str> = a_contents.lines().collect();
         let b_contents = fs::read_to_string(&day.input_b)
-            .expect(&format!("Day {}b input file should be present", idx + 1));
-        let input_b = b_contents.lines().collect();
+            .unwrap_or_else(|_| panic!("Day {}b input file should be present", idx + 1));
+        let input_b: Vec<&str> = b_contents.lines().collect();
         println!(
             "Day {}: {} | {}",
             idx + 1,